@@ -15,8 +15,15 @@ pub enum Action {
     IncreasePreview,
     DecreasePreview,
     CopyToClipboard,
+    ToggleMark,
+    MarkAll,
+    ClearMarks,
+    CycleSort,
+    ReverseSort,
+    ToggleErrors,
     Search,
     Filter,
+    Accept,
     Quit,
     Backspace,
 }
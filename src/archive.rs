@@ -0,0 +1,84 @@
+use std::{fs::File, io::Read, path::Path};
+
+use anyhow::Result;
+use flate2::read::GzDecoder;
+use tar::EntryType;
+use zip::ZipArchive;
+
+// joins an archive path and a member path, e.g. `release.zip!/assets/logo.png`
+pub const MEMBER_SEPARATOR: &str = "!/";
+
+const ZIP_EXTENSIONS: [&str; 4] = [".zip", ".jar", ".war", ".ear"];
+const TAR_EXTENSIONS: [&str; 1] = [".tar"];
+const TAR_GZ_EXTENSIONS: [&str; 2] = [".tar.gz", ".tgz"];
+
+enum ArchiveKind {
+    Zip,
+    Tar,
+    TarGz,
+}
+
+fn archive_kind(path: &Path) -> Option<ArchiveKind> {
+    let file_name = path.file_name()?.to_string_lossy().to_lowercase();
+    let has_extension = |extensions: &[&str]| extensions.iter().any(|ext| file_name.ends_with(ext));
+
+    if has_extension(&ZIP_EXTENSIONS) {
+        Some(ArchiveKind::Zip)
+    } else if has_extension(&TAR_EXTENSIONS) {
+        Some(ArchiveKind::Tar)
+    } else if has_extension(&TAR_GZ_EXTENSIONS) {
+        Some(ArchiveKind::TarGz)
+    } else {
+        None
+    }
+}
+
+pub fn is_archive(path: &Path) -> bool {
+    archive_kind(path).is_some()
+}
+
+pub fn is_member_path(entry: &str) -> bool {
+    entry
+        .split_once(MEMBER_SEPARATOR)
+        .is_some_and(|(archive, _)| is_archive(Path::new(archive)))
+}
+
+// paths of the regular files stored in the archive, directories are left out
+pub fn list_members(path: &Path) -> Result<Vec<String>> {
+    let file = File::open(path)?;
+
+    match archive_kind(path) {
+        Some(ArchiveKind::Zip) => list_zip_members(file),
+        Some(ArchiveKind::Tar) => list_tar_members(file),
+        Some(ArchiveKind::TarGz) => list_tar_members(GzDecoder::new(file)),
+        None => Ok(Vec::new()),
+    }
+}
+
+fn list_zip_members(file: File) -> Result<Vec<String>> {
+    // only reads the central directory, nothing gets decompressed
+    let archive = ZipArchive::new(file)?;
+
+    Ok(archive
+        .file_names()
+        .filter(|name| !name.ends_with('/'))
+        .map(str::to_string)
+        .collect())
+}
+
+fn list_tar_members<R: Read>(reader: R) -> Result<Vec<String>> {
+    let mut archive = tar::Archive::new(reader);
+    let mut members = Vec::new();
+
+    for entry in archive.entries()? {
+        let entry = entry?;
+        if entry.header().entry_type() == EntryType::Regular {
+            // `tar -C dir .` stores everything under `./`
+            let path = entry.path()?;
+            let path = path.strip_prefix(".").unwrap_or(&path);
+            members.push(path.to_string_lossy().to_string());
+        }
+    }
+
+    Ok(members)
+}
@@ -1,4 +1,5 @@
 use std::{
+    collections::HashMap,
     env,
     num::NonZeroUsize,
     path::{Path, PathBuf},
@@ -8,12 +9,18 @@ use std::{
 
 use anyhow::{Context, Result, anyhow};
 use clap::{ArgAction, Parser};
+use globset::GlobBuilder;
 use regex::bytes::RegexBuilder;
 
 use crate::{
     config::Config,
+    entry::RelativeTo,
     exit_codes::ExitCode,
     file_system::{self},
+    filter::{SizeFilter, TimeFilter},
+    index,
+    pattern::PatternSet,
+    sort::SortMode,
     tui::AppEvent,
     walk::Walker,
 };
@@ -34,9 +41,10 @@ pub struct Args {
     #[arg(
         short = 'k',
         long = "kind",
-        help = "Filter by type: file (f/file) or directory (d/dir)"
+        action = ArgAction::Append,
+        help = "Filter by type, can be repeated to match any of them"
     )]
-    pub kind: Option<Type>,
+    pub kind: Vec<Type>,
 
     #[arg(short = 'H', long = "hidden", default_value_t = false)]
     pub show_hidden: bool,
@@ -49,6 +57,194 @@ pub struct Args {
 
     #[arg(short = 't', long)]
     pub threads: Option<NonZeroUsize>,
+
+    #[arg(
+        short = 'c',
+        long = "contents",
+        help = "Search inside files and list every matching line"
+    )]
+    pub search_contents: bool,
+
+    #[arg(
+        short = 'p',
+        long = "full-path",
+        help = "Match the pattern against the path relative to the search root"
+    )]
+    pub full_path: bool,
+
+    #[arg(
+        short = 'g',
+        long = "glob",
+        help = "Treat the pattern as a glob (*, **, {a,b}) instead of a regex"
+    )]
+    pub glob: bool,
+
+    #[arg(
+        short = 'F',
+        long = "fixed-strings",
+        conflicts_with = "glob",
+        help = "Treat the pattern as a literal string instead of a regex"
+    )]
+    pub fixed_strings: bool,
+
+    #[arg(
+        long = "and",
+        action = ArgAction::Append,
+        value_name = "pattern",
+        help = "Additional pattern that must also match, can be repeated"
+    )]
+    pub and_patterns: Vec<String>,
+
+    #[arg(
+        long = "not",
+        action = ArgAction::Append,
+        value_name = "pattern",
+        help = "Pattern that must not match, can be repeated"
+    )]
+    pub not_patterns: Vec<String>,
+
+    #[arg(
+        short = 'e',
+        long = "extension",
+        action = ArgAction::Append,
+        value_name = "ext",
+        help = "Only show files with this extension, can be repeated"
+    )]
+    pub extensions: Vec<String>,
+
+    #[arg(
+        short = 'S',
+        long = "size",
+        action = ArgAction::Append,
+        allow_hyphen_values = true,
+        value_name = "size",
+        help = "Limit file size: +10M (at least), -4k (at most) or 512b (exactly), can be repeated"
+    )]
+    pub size: Vec<SizeFilter>,
+
+    #[arg(
+        long = "changed-within",
+        value_name = "time",
+        value_parser = TimeFilter::after,
+        help = "Only entries modified within a duration (2h, 3d) or since a date (2024-05-01)"
+    )]
+    pub changed_within: Option<TimeFilter>,
+
+    #[arg(
+        long = "changed-before",
+        value_name = "time",
+        value_parser = TimeFilter::before,
+        help = "Only entries modified longer ago than a duration (2h, 3d) or before a date (2024-05-01)"
+    )]
+    pub changed_before: Option<TimeFilter>,
+
+    #[arg(
+        short = 'E',
+        long = "exclude",
+        action = ArgAction::Append,
+        value_name = "glob",
+        help = "Skip entries matching this glob, excluded directories are not descended into"
+    )]
+    pub exclude: Vec<String>,
+
+    #[arg(
+        long = "no-ignore",
+        help = "Don't respect any ignore file (.rfuiignore, .ignore, .gitignore, git exclude, global excludes)"
+    )]
+    pub no_ignore: bool,
+
+    #[arg(
+        long = "no-ignore-vcs",
+        help = "Don't respect .gitignore, .git/info/exclude and the global git excludes file"
+    )]
+    pub no_ignore_vcs: bool,
+
+    #[arg(long = "no-ignore-dot", help = "Don't respect .ignore files")]
+    pub no_ignore_dot: bool,
+
+    #[arg(
+        long = "no-global-ignore",
+        help = "Don't respect the global git excludes file (core.excludesFile)"
+    )]
+    pub no_global_ignore: bool,
+
+    #[arg(
+        short = 'u',
+        long = "unrestricted",
+        help = "Disable all filtering: same as --no-ignore --hidden"
+    )]
+    pub unrestricted: bool,
+
+    #[arg(short = 'L', long = "follow", help = "Follow symbolic links")]
+    pub follow: bool,
+
+    #[arg(
+        long = "one-file-system",
+        overrides_with = "cross_filesystems",
+        help = "Don't descend into other filesystems (default)"
+    )]
+    pub one_file_system: bool,
+
+    #[arg(
+        long = "cross-filesystems",
+        overrides_with = "one_file_system",
+        help = "Descend into mount points on other filesystems"
+    )]
+    pub cross_filesystems: bool,
+
+    #[arg(
+        short = 'T',
+        long = "type",
+        action = ArgAction::Append,
+        value_name = "type",
+        help = "Only files of this type (rust, web, ...), prefix with ! to exclude, can be repeated"
+    )]
+    pub file_types: Vec<String>,
+
+    #[arg(
+        long = "search-archives",
+        help = "Also match the members of zip, jar, tar and tar.gz files"
+    )]
+    pub search_archives: bool,
+
+    #[arg(
+        short = 'w',
+        long = "watch",
+        conflicts_with_all = ["search_contents", "search_archives"],
+        help = "Keep watching the search roots and update results as entries appear or disappear"
+    )]
+    pub watch: bool,
+
+    #[arg(
+        long = "sort",
+        value_name = "mode",
+        help = "Order results by score (default), name, path, size, mtime or depth"
+    )]
+    pub sort: Option<SortMode>,
+
+    #[arg(short = 'r', long = "reverse", help = "Reverse the result order")]
+    pub reverse: bool,
+
+    #[arg(
+        short = 'a',
+        long = "absolute-path",
+        conflicts_with = "relative_to",
+        help = "Show every result as an absolute path"
+    )]
+    pub absolute_path: bool,
+
+    #[arg(
+        long = "relative-to",
+        value_name = "base",
+        help = "Show results relative to their search root or to the current directory"
+    )]
+    pub relative_to: Option<RelativeTo>,
+
+    #[arg(
+        long = "no-index",
+        help = "Always walk the disk, even when an index built with `rfui --index` covers the paths"
+    )]
+    pub no_index: bool,
 }
 
 #[derive(Debug, Clone, clap::ValueEnum)]
@@ -58,6 +254,48 @@ pub enum Type {
 
     #[value(alias = "f", alias = "file")]
     File,
+
+    #[value(alias = "l", alias = "link")]
+    Symlink,
+
+    #[value(alias = "x", alias = "exe")]
+    Executable,
+
+    #[value(alias = "e")]
+    Empty,
+
+    #[value(alias = "s")]
+    Socket,
+
+    #[value(alias = "p", alias = "fifo")]
+    Pipe,
+
+    #[value(alias = "b", alias = "block")]
+    BlockDevice,
+
+    #[value(alias = "c", alias = "char")]
+    CharDevice,
+}
+
+impl Args {
+    pub fn paths(&self) -> &[PathBuf] {
+        &self.path
+    }
+
+    // the same flags, matching everything under the default paths
+    pub fn listing_all(mut self) -> Self {
+        self.pattern = ".".to_string();
+        self.path.clear();
+        self
+    }
+
+    // searches that don't name any path fall back to these instead of the cwd
+    pub fn with_default_paths(mut self, paths: &[PathBuf]) -> Self {
+        if self.path.is_empty() {
+            self.path = paths.to_vec();
+        }
+        self
+    }
 }
 
 fn is_valid_directory(path: &Path) -> Result<()> {
@@ -87,19 +325,26 @@ fn get_search_paths(path: &Vec<PathBuf>) -> Result<Vec<PathBuf>> {
         return Ok(vec![normalize_path(&current_dir)]);
     };
 
-    Ok(paths
+    Ok(paths.iter().filter_map(|path| search_path(path)).collect())
+}
+
+// the paths get_search_paths leaves out
+pub fn missing_paths(paths: &[PathBuf]) -> Vec<&PathBuf> {
+    paths
         .iter()
-        .filter_map(|path| {
-            let expanded_path = expand_tilde(path.to_string_lossy().to_string());
-            let path = Path::new(&expanded_path);
-
-            if file_system::is_existing_dir(path) {
-                Some(normalize_path(path))
-            } else {
-                None
-            }
-        })
-        .collect())
+        .filter(|path| search_path(path).is_none())
+        .collect()
+}
+
+fn search_path(path: &Path) -> Option<PathBuf> {
+    let expanded_path = expand_tilde(path.to_string_lossy().to_string());
+    let path = Path::new(&expanded_path);
+
+    if file_system::is_existing_dir(path) {
+        Some(normalize_path(path))
+    } else {
+        None
+    }
 }
 
 pub fn expand_tilde(path: String) -> String {
@@ -123,22 +368,67 @@ pub fn parse_input_args(input: &str) -> Result<Args, clap::Error> {
     Args::try_parse_from(full_args)
 }
 
-pub fn build_and_scan(args: Args, tx: Sender<AppEvent>, should_stop_flag: Arc<AtomicBool>) -> Result<ExitCode> {
+pub fn build_and_scan(
+    args: Args,
+    type_definitions: HashMap<String, Vec<String>>,
+    tx: Sender<AppEvent>,
+    should_stop_flag: Arc<AtomicBool>,
+) -> Result<ExitCode> {
     let search_paths =
         get_search_paths(&args.path).with_context(|| "Failed to get search paths")?;
 
-    let regexp = regex_builder(&args).with_context(|| "Failed building regex pattern")?;
+    let patterns = pattern_builder(&args).with_context(|| "Failed building regex pattern")?;
+
+    let use_index = !args.no_index;
+    let config = Config::build(args, type_definitions);
+
+    if use_index && index::supports(&config) {
+        if let Some(indexes) = index::find_all(&search_paths) {
+            return index::scan(indexes, &config, &patterns, tx, should_stop_flag);
+        }
+    }
 
-    let config = Config::build(args);
     let walker = Walker::new(config);
 
-    walker.scan(search_paths, regexp, tx, should_stop_flag)
+    walker.scan(search_paths, patterns, tx, should_stop_flag)
+}
+
+fn pattern_builder(args: &Args) -> Result<PatternSet> {
+    let build_all = |patterns: &[String]| {
+        patterns
+            .iter()
+            .map(|pattern| regex_builder(args, pattern))
+            .collect::<Result<Vec<_>>>()
+    };
+
+    Ok(PatternSet::new(
+        regex_builder(args, &args.pattern)?,
+        build_all(&args.and_patterns)?,
+        build_all(&args.not_patterns)?,
+    ))
 }
 
-fn regex_builder(args: &Args) -> Result<regex::bytes::Regex> {
-    RegexBuilder::new(&args.pattern)
+fn regex_builder(args: &Args, pattern: &str) -> Result<regex::bytes::Regex> {
+    let pattern = if args.glob {
+        glob_to_regex(pattern, args.full_path)?
+    } else if args.fixed_strings {
+        regex::escape(pattern)
+    } else {
+        pattern.to_string()
+    };
+
+    RegexBuilder::new(&pattern)
         .case_insensitive(!&args.case_sensitive)
         .dot_matches_new_line(true)
         .build()
         .map_err(|e| anyhow!("{}", e))
 }
+
+fn glob_to_regex(pattern: &str, full_path: bool) -> Result<String> {
+    // `*` must not cross directories when matching full paths, that's what `**` is for
+    GlobBuilder::new(pattern)
+        .literal_separator(full_path)
+        .build()
+        .map(|glob| glob.regex().to_string())
+        .map_err(|e| anyhow!("{}", e))
+}
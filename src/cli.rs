@@ -0,0 +1,115 @@
+use std::path::PathBuf;
+
+use clap::{
+    ArgMatches, CommandFactory, FromArgMatches, Parser, error::ErrorKind, parser::ValueSource,
+};
+
+use crate::{args::Args, index::IndexAction};
+
+// the command line rfui is started with, the same search flags work here and in the ui
+#[derive(Parser, Debug)]
+#[command(version, about, long_about = None)]
+pub struct Cli {
+    #[arg(
+        long = "index",
+        value_name = "action",
+        help = "Build or update the search index for the given paths, then exit"
+    )]
+    pub index: Option<IndexAction>,
+
+    #[arg(
+        long = "print",
+        conflicts_with = "index",
+        help = "Print matches to stdout instead of opening the ui (default when stdout isn't a terminal)"
+    )]
+    pub print: bool,
+
+    #[arg(
+        long = "pick",
+        conflicts_with_all = ["index", "print"],
+        help = "Open the ui and print the entry picked with Enter to stdout, e.g. `vim $(rfui --pick)`"
+    )]
+    pub pick: bool,
+
+    #[arg(
+        long = "query",
+        value_name = "input",
+        conflicts_with_all = ["index", "print"],
+        help = "Open the ui with this search typed in and already running, e.g. \"config -k f\""
+    )]
+    pub query: Option<String>,
+
+    #[arg(
+        long = "filter",
+        value_name = "text",
+        conflicts_with_all = ["index", "print"],
+        help = "Fuzzy filter the results of the first search with this text"
+    )]
+    pub filter: Option<String>,
+
+    #[command(flatten)]
+    pub search: Args,
+}
+
+impl Cli {
+    pub fn from_env() -> Self {
+        // without a pattern rfui just opens the ui, so it's only required when typed in there.
+        // changing an arg re-adds it at the end, so both positionals need their index again.
+        // the flattened search args turn --help off, it's only free to use out here
+        let mut command = Self::command()
+            .disable_help_flag(false)
+            .mut_arg("pattern", |arg| {
+                arg.required(false)
+                    .default_value("")
+                    .hide_default_value(true)
+                    .index(1)
+            })
+            .mut_arg("path", |arg| arg.index(2));
+        let matches = command.get_matches_mut();
+
+        let cli = Self::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+
+        // search flags that wouldn't reach any search are an error, not silently dropped
+        if let Some(flag) = search_flag(&matches) {
+            let message = if cli.index.is_some() {
+                format!("{} can't be used with --index", flag)
+            } else if cli.query.is_some() {
+                format!("{} can't be used with --query, put it in the query instead", flag)
+            } else if !cli.pick && cli.search.pattern.is_empty() {
+                format!("{} needs a pattern to search for, `.` matches everything", flag)
+            } else {
+                return cli;
+            };
+            command.error(ErrorKind::ArgumentConflict, message).exit();
+        }
+
+        cli
+    }
+
+    // with --index, --query or --pick the positionals are only paths
+    pub fn has_search(&self) -> bool {
+        self.index.is_none()
+            && self.query.is_none()
+            && !self.pick
+            && !self.search.pattern.is_empty()
+    }
+
+    pub fn paths(&self) -> Vec<PathBuf> {
+        let pattern = (!self.has_search() && !self.search.pattern.is_empty())
+            .then(|| PathBuf::from(&self.search.pattern));
+        pattern.into_iter().chain(self.search.paths().iter().cloned()).collect()
+    }
+}
+
+// the first search flag given on the command line, e.g. `-k`
+fn search_flag(matches: &ArgMatches) -> Option<String> {
+    Args::command()
+        .get_arguments()
+        .filter(|arg| !arg.is_positional())
+        .find(|arg| matches.value_source(arg.get_id().as_str()) == Some(ValueSource::CommandLine))
+        .map(|arg| match (arg.get_long(), arg.get_short()) {
+            (Some(long), _) => format!("--{}", long),
+            (None, Some(short)) => format!("-{}", short),
+            (None, None) => arg.get_id().to_string(),
+        })
+}
@@ -1,26 +1,79 @@
 use crate::args::{Args, Type};
-use std::num::NonZeroUsize;
+use crate::entry::PathStyle;
+use crate::filter::{SizeFilter, TimeFilter};
+use std::{collections::HashMap, num::NonZeroUsize};
 
 #[derive(Debug)]
 pub struct Config {
-    pub kind: Option<Type>,
+    pub kind: Vec<Type>,
     pub show_hidden: bool,
     pub max_depth: Option<usize>,
     pub case_sensitive: bool,
     pub threads: usize,
+    pub search_contents: bool,
+    pub full_path: bool,
+    pub extensions: Vec<String>,
+    pub size_filters: Vec<SizeFilter>,
+    pub time_filters: Vec<TimeFilter>,
+    pub excludes: Vec<String>,
+    pub read_dot_ignore: bool,
+    pub read_vcs_ignore: bool,
+    pub read_global_ignore: bool,
+    pub read_rfui_ignore: bool,
+    pub follow_links: bool,
+    pub same_file_system: bool,
+    pub file_types: Vec<String>,
+    pub type_definitions: HashMap<String, Vec<String>>,
+    pub search_archives: bool,
+    pub watch: bool,
+    pub path_style: PathStyle,
 }
 
 impl Config {
-    pub fn build(args: Args) -> Self {
+    pub fn build(args: Args, type_definitions: HashMap<String, Vec<String>>) -> Self {
         let case_sensitive = args.case_sensitive || has_uppercase_char(&args.pattern);
         let threads = args.threads.unwrap_or_else(num_of_threads).get();
+        let extensions = args
+            .extensions
+            .iter()
+            .map(|ext| ext.trim_start_matches('.').to_lowercase())
+            .collect();
+        let no_ignore = args.no_ignore || args.unrestricted;
+        let read_vcs_ignore = !no_ignore && !args.no_ignore_vcs;
+        let time_filters = args
+            .changed_within
+            .into_iter()
+            .chain(args.changed_before)
+            .collect();
+        let path_style = match args.relative_to {
+            Some(relative_to) => PathStyle::RelativeTo(relative_to),
+            None if args.absolute_path => PathStyle::Absolute,
+            None => PathStyle::Default,
+        };
 
         Self {
             kind: args.kind,
-            show_hidden: args.show_hidden,
+            show_hidden: args.show_hidden || args.unrestricted,
             max_depth: args.max_depth,
             case_sensitive,
             threads,
+            search_contents: args.search_contents,
+            full_path: args.full_path,
+            extensions,
+            size_filters: args.size,
+            time_filters,
+            excludes: args.exclude,
+            read_dot_ignore: !no_ignore && !args.no_ignore_dot,
+            read_vcs_ignore,
+            read_global_ignore: read_vcs_ignore && !args.no_global_ignore,
+            read_rfui_ignore: !no_ignore,
+            follow_links: args.follow,
+            same_file_system: !args.cross_filesystems,
+            file_types: args.file_types,
+            type_definitions,
+            search_archives: args.search_archives,
+            watch: args.watch,
+            path_style,
         }
     }
 }
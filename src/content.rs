@@ -0,0 +1,48 @@
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader},
+    path::Path,
+};
+
+use crate::{pattern::PatternSet, preview};
+
+pub struct ContentMatch {
+    pub line_number: usize,
+    pub column: usize,
+    pub text: String,
+}
+
+impl ContentMatch {
+    // goes after the path: `path:line:col:text`
+    pub fn suffix(&self) -> String {
+        format!(":{}:{}:{}", self.line_number, self.column, self.text)
+    }
+}
+
+// binary files are skipped, files that can't be read are an error
+pub fn search_file(path: &Path, patterns: &PatternSet) -> io::Result<Vec<ContentMatch>> {
+    if !preview::should_preview_with_bat(&path.to_string_lossy()) {
+        return Ok(Vec::new());
+    }
+
+    let mut reader = BufReader::new(File::open(path)?);
+
+    // same heuristic as git/grep: a NUL byte in the first chunk means binary
+    if reader.fill_buf()?.contains(&0) {
+        return Ok(Vec::new());
+    }
+
+    Ok(reader
+        .split(b'\n')
+        .map_while(|line| line.ok())
+        .enumerate()
+        .filter_map(|(index, line)| {
+            let found = patterns.find(&line)?;
+            Some(ContentMatch {
+                line_number: index + 1,
+                column: found.start() + 1,
+                text: String::from_utf8_lossy(&line).trim().to_string(),
+            })
+        })
+        .collect())
+}
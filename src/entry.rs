@@ -0,0 +1,200 @@
+use std::{
+    env,
+    path::{Component, Path, PathBuf},
+};
+
+use crate::{content::ContentMatch, file_system};
+
+// a search result, `target` keeps the old cwd relative form so preview, sorting and the
+// clipboard can find the file no matter how `display` is written
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Entry {
+    pub display: String,
+    pub target: String,
+    // the line of a content match, its `:line:col:text` comes after the path in `target`
+    pub line: Option<usize>,
+    path_len: usize,
+    // the `[root] ` in front of `display` with several roots, root relative paths
+    // keep theirs since they don't say which root they're in otherwise
+    label_len: usize,
+}
+
+impl Entry {
+    pub fn new(display: String, target: String) -> Self {
+        Self {
+            display,
+            path_len: target.len(),
+            target,
+            line: None,
+            label_len: 0,
+        }
+    }
+
+    // `target` without the content match, archive members keep their `!/member`
+    pub fn path(&self) -> &str {
+        &self.target[..self.path_len]
+    }
+
+    // `display` without the root label, for output that scripts read
+    pub fn unlabeled(&self) -> &str {
+        &self.display[self.label_len..]
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum RelativeTo {
+    Root,
+    Cwd,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PathStyle {
+    // relative to the cwd when inside it, absolute otherwise
+    #[default]
+    Default,
+    Absolute,
+    RelativeTo(RelativeTo),
+}
+
+pub struct PathFormat {
+    style: PathStyle,
+    current_dir: PathBuf,
+    // most specific root first, labels only when there's more than one root
+    roots: Vec<(PathBuf, Option<String>)>,
+}
+
+impl PathFormat {
+    pub fn new(style: PathStyle, roots: &[PathBuf]) -> Self {
+        let current_dir = env::current_dir().unwrap_or_default();
+        let labels = root_labels(roots);
+
+        let mut roots = roots
+            .iter()
+            .map(|root| absolute(root, &current_dir))
+            .zip(labels)
+            .collect::<Vec<_>>();
+        roots.sort_by_key(|(root, _)| std::cmp::Reverse(root.components().count()));
+
+        Self {
+            style,
+            current_dir,
+            roots,
+        }
+    }
+
+    // `suffix` carries the `:line:col:text` or `!/member` part of the result
+    pub fn entry(&self, path: &Path, suffix: &str) -> Entry {
+        let target = format!("{}{}", file_system::get_display_path(path), suffix);
+
+        let absolute_path = absolute(path, &self.current_dir);
+        let root = self
+            .roots
+            .iter()
+            .find(|(root, _)| absolute_path.starts_with(root));
+
+        let display_path = match self.style {
+            PathStyle::Default => file_system::get_display_path(path),
+            PathStyle::Absolute => absolute_path.to_string_lossy().to_string(),
+            PathStyle::RelativeTo(RelativeTo::Cwd) => relative_to(&absolute_path, &self.current_dir)
+                .to_string_lossy()
+                .to_string(),
+            PathStyle::RelativeTo(RelativeTo::Root) => root
+                .and_then(|(root, _)| absolute_path.strip_prefix(root).ok())
+                .unwrap_or(&absolute_path)
+                .to_string_lossy()
+                .to_string(),
+        };
+
+        let label = match root.and_then(|(_, label)| label.as_ref()) {
+            Some(label) => format!("[{}] ", label),
+            None => String::new(),
+        };
+        let display = format!("{}{}{}", label, display_path, suffix);
+
+        let label_len = match self.style {
+            PathStyle::RelativeTo(RelativeTo::Root) => 0,
+            _ => label.len(),
+        };
+
+        Entry {
+            label_len,
+            ..Entry::new(display, target)
+        }
+    }
+
+    pub fn content_entry(&self, path: &Path, content_match: &ContentMatch) -> Entry {
+        let mut entry = self.entry(path, "");
+        let suffix = content_match.suffix();
+        entry.display.push_str(&suffix);
+        entry.target.push_str(&suffix);
+        entry.line = Some(content_match.line_number);
+        entry
+    }
+}
+
+// the root's directory name, or the whole root when two of them share a name
+fn root_labels(roots: &[PathBuf]) -> Vec<Option<String>> {
+    if roots.len() < 2 {
+        return vec![None; roots.len()];
+    }
+
+    let names = roots
+        .iter()
+        .map(|root| {
+            root.file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_else(|| full_label(root))
+        })
+        .collect::<Vec<_>>();
+
+    names
+        .iter()
+        .zip(roots)
+        .map(|(name, root)| {
+            if names.iter().filter(|other| *other == name).count() > 1 {
+                Some(full_label(root))
+            } else {
+                Some(name.clone())
+            }
+        })
+        .collect()
+}
+
+// `./` reads better as `.`, but `/` has to stay
+fn full_label(root: &Path) -> String {
+    let label = root.to_string_lossy();
+    match label.trim_end_matches(['/', '\\']) {
+        "" => label.to_string(),
+        trimmed => trimmed.to_string(),
+    }
+}
+
+// lexically, like the roots were typed: `src/../tests` is `tests` even if `src` is a symlink
+fn absolute(path: &Path, current_dir: &Path) -> PathBuf {
+    let mut absolute = PathBuf::new();
+    for component in current_dir.join(path).components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                absolute.pop();
+            }
+            component => absolute.push(component),
+        }
+    }
+
+    absolute
+}
+
+fn relative_to(path: &Path, base: &Path) -> PathBuf {
+    let common = path
+        .components()
+        .zip(base.components())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    base.components()
+        .skip(common)
+        .map(|_| Component::ParentDir)
+        .chain(path.components().skip(common))
+        .collect()
+}
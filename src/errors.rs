@@ -0,0 +1,140 @@
+use std::path::Path;
+
+use ratatui::{
+    Frame,
+    layout::{Alignment, Rect},
+    style::{Color, Style, Stylize},
+    text::Line,
+    widgets::{Block, BorderType, Paragraph, Wrap},
+};
+
+use crate::file_system;
+
+// an entry the search had to skip, and why
+#[derive(Debug, Clone)]
+pub struct WalkError {
+    pub path: String,
+    pub reason: String,
+}
+
+impl WalkError {
+    pub fn new(path: &Path, reason: impl ToString) -> Self {
+        Self {
+            path: file_system::get_display_path(path),
+            reason: reason.to_string(),
+        }
+    }
+}
+
+impl From<&ignore::Error> for WalkError {
+    fn from(error: &ignore::Error) -> Self {
+        Self {
+            path: error_path(error)
+                .map(file_system::get_display_path)
+                .unwrap_or_default(),
+            reason: error_reason(error),
+        }
+    }
+}
+
+fn error_path(error: &ignore::Error) -> Option<&Path> {
+    match error {
+        ignore::Error::WithPath { path, .. } => Some(path),
+        ignore::Error::Loop { child, .. } => Some(child),
+        ignore::Error::WithDepth { err, .. } | ignore::Error::WithLineNumber { err, .. } => {
+            error_path(err)
+        }
+        ignore::Error::Partial(errors) => errors.iter().find_map(error_path),
+        _ => None,
+    }
+}
+
+// the path is shown on its own, so only keep what went wrong
+fn error_reason(error: &ignore::Error) -> String {
+    match error {
+        ignore::Error::WithPath { err, .. } | ignore::Error::WithDepth { err, .. } => {
+            error_reason(err)
+        }
+        ignore::Error::Loop { ancestor, .. } => format!(
+            "symlink loop, points back to {}",
+            file_system::get_display_path(ancestor)
+        ),
+        ignore::Error::Partial(errors) => errors
+            .iter()
+            .map(error_reason)
+            .collect::<Vec<_>>()
+            .join("; "),
+        _ => error.to_string(),
+    }
+}
+
+pub struct ErrorPanel {
+    errors: Vec<WalkError>,
+    vertical_scroll: u16,
+    pub is_visible: bool,
+}
+
+impl ErrorPanel {
+    pub fn new() -> Self {
+        Self {
+            errors: Vec::new(),
+            vertical_scroll: 0,
+            is_visible: false,
+        }
+    }
+
+    pub fn push(&mut self, error: WalkError) {
+        self.errors.push(error);
+    }
+
+    pub fn clear(&mut self) {
+        self.errors.clear();
+        self.vertical_scroll = 0;
+    }
+
+    pub fn count(&self) -> usize {
+        self.errors.len()
+    }
+
+    pub fn toggle(&mut self) {
+        self.is_visible = !self.is_visible;
+    }
+
+    pub fn scroll_up(&mut self) {
+        self.vertical_scroll = self.vertical_scroll.saturating_sub(2);
+    }
+
+    pub fn scroll_down(&mut self) {
+        self.vertical_scroll = self.vertical_scroll.saturating_add(2);
+    }
+
+    pub fn render(&self, frame: &mut Frame, area: Rect) {
+        let lines = if self.errors.is_empty() {
+            vec![Line::from("Nothing was skipped").fg(Color::DarkGray)]
+        } else {
+            self.errors
+                .iter()
+                .flat_map(|error| {
+                    [
+                        Line::from(error.path.as_str()).fg(Color::Yellow).bold(),
+                        Line::from(format!("  {}", error.reason)),
+                    ]
+                })
+                .collect()
+        };
+
+        let errors_block = Block::bordered()
+            .title(format!(" Errors ({}) ", self.errors.len()).bold().red())
+            .title_bottom("  Ctrl+J/K scroll • Ctrl+E close  ")
+            .title_alignment(Alignment::Center)
+            .border_type(BorderType::Rounded)
+            .border_style(Style::default().fg(Color::Red));
+
+        let errors_paragraph = Paragraph::new(lines)
+            .block(errors_block)
+            .wrap(Wrap { trim: false })
+            .scroll((self.vertical_scroll, 0));
+
+        frame.render_widget(errors_paragraph, area);
+    }
+}
@@ -1,6 +1,7 @@
 #[derive(Debug)]
 pub enum ExitCode {
     Success,
+    NoMatches,
     KilledBySigint,
     GeneralError(String),
 }
@@ -9,7 +10,9 @@ impl From<ExitCode> for i32 {
     fn from(code: ExitCode) -> Self {
         match code {
             ExitCode::Success => 0,
-            ExitCode::GeneralError(_) => 1,
+            // same as grep: 1 for no matches, 2 for errors
+            ExitCode::NoMatches => 1,
+            ExitCode::GeneralError(_) => 2,
             ExitCode::KilledBySigint => 130,
         }
     }
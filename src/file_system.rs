@@ -1,5 +1,11 @@
 use normpath::PathExt;
-use std::{borrow::Cow, env, ffi::OsStr, path::Path};
+use std::{
+    borrow::Cow,
+    env,
+    ffi::OsStr,
+    fs::{FileType, Metadata},
+    path::Path,
+};
 
 pub fn is_existing_dir(path: &Path) -> bool {
     path.is_dir() && (path.file_name().is_some() || path.normalize().is_ok())
@@ -11,6 +17,10 @@ pub fn get_relative_path(path: &Path) -> Option<String> {
         .map(|p| p.to_string_lossy().to_string())
 }
 
+pub fn get_display_path(path: &Path) -> String {
+    get_relative_path(path).unwrap_or_else(|| path.to_string_lossy().to_string())
+}
+
 #[cfg(unix)]
 pub fn osstr_to_bytes(entry: &OsStr) -> Cow<[u8]> {
     use std::os::unix::ffi::OsStrExt;
@@ -26,3 +36,61 @@ pub fn osstr_to_bytes(entry: &OsStr) -> Cow<[u8]> {
         Cow::Borrowed(s) => Cow::Borrowed(s.as_bytes()),
     }
 }
+
+#[cfg(unix)]
+pub fn is_executable(_path: &Path, metadata: &Metadata) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    metadata.permissions().mode() & 0o111 != 0
+}
+
+#[cfg(windows)]
+pub fn is_executable(path: &Path, _metadata: &Metadata) -> bool {
+    // no exec bit on windows, go by what the shell would run
+    path.extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .is_some_and(|ext| matches!(ext.as_str(), "exe" | "com" | "bat" | "cmd" | "ps1"))
+}
+
+#[cfg(unix)]
+pub fn is_socket(file_type: &FileType) -> bool {
+    use std::os::unix::fs::FileTypeExt;
+    file_type.is_socket()
+}
+
+#[cfg(windows)]
+pub fn is_socket(_file_type: &FileType) -> bool {
+    false
+}
+
+#[cfg(unix)]
+pub fn is_pipe(file_type: &FileType) -> bool {
+    use std::os::unix::fs::FileTypeExt;
+    file_type.is_fifo()
+}
+
+#[cfg(windows)]
+pub fn is_pipe(_file_type: &FileType) -> bool {
+    false
+}
+
+#[cfg(unix)]
+pub fn is_block_device(file_type: &FileType) -> bool {
+    use std::os::unix::fs::FileTypeExt;
+    file_type.is_block_device()
+}
+
+#[cfg(windows)]
+pub fn is_block_device(_file_type: &FileType) -> bool {
+    false
+}
+
+#[cfg(unix)]
+pub fn is_char_device(file_type: &FileType) -> bool {
+    use std::os::unix::fs::FileTypeExt;
+    file_type.is_char_device()
+}
+
+#[cfg(windows)]
+pub fn is_char_device(_file_type: &FileType) -> bool {
+    false
+}
@@ -0,0 +1,109 @@
+use std::{
+    str::FromStr,
+    sync::LazyLock,
+    time::SystemTime,
+};
+
+use anyhow::{Error, anyhow};
+use regex::Regex;
+
+static SIZE_CAPTURE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)^([+-]?)(\d+)([a-z]*)$").unwrap());
+
+const KILO: u64 = 1000;
+const KIBI: u64 = 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeFilter {
+    Before(SystemTime),
+    After(SystemTime),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SizeFilter {
+    Min(u64),
+    Max(u64),
+    Equals(u64),
+}
+
+impl SizeFilter {
+    pub fn is_within(&self, size: u64) -> bool {
+        match *self {
+            SizeFilter::Min(limit) => size >= limit,
+            SizeFilter::Max(limit) => size <= limit,
+            SizeFilter::Equals(limit) => size == limit,
+        }
+    }
+}
+
+impl FromStr for SizeFilter {
+    type Err = Error;
+
+    fn from_str(raw: &str) -> Result<Self, Self::Err> {
+        let captures = SIZE_CAPTURE
+            .captures(raw)
+            .ok_or_else(|| anyhow!("Invalid size '{}', expected e.g. +10M or -4k", raw))?;
+
+        let quantity = captures[2]
+            .parse::<u64>()
+            .map_err(|e| anyhow!("Invalid size '{}': {}", raw, e))?;
+
+        let multiplier = match captures[3].to_ascii_lowercase().as_str() {
+            "" | "b" => 1,
+            "k" | "kb" => KILO,
+            "ki" | "kib" => KIBI,
+            "m" | "mb" => KILO.pow(2),
+            "mi" | "mib" => KIBI.pow(2),
+            "g" | "gb" => KILO.pow(3),
+            "gi" | "gib" => KIBI.pow(3),
+            "t" | "tb" => KILO.pow(4),
+            "ti" | "tib" => KIBI.pow(4),
+            unit => return Err(anyhow!("Unknown size unit '{}'", unit)),
+        };
+
+        let size = quantity
+            .checked_mul(multiplier)
+            .ok_or_else(|| anyhow!("Size '{}' is too large", raw))?;
+
+        Ok(match &captures[1] {
+            "+" => SizeFilter::Min(size),
+            "-" => SizeFilter::Max(size),
+            _ => SizeFilter::Equals(size),
+        })
+    }
+}
+
+impl TimeFilter {
+    pub fn before(raw: &str) -> Result<Self, Error> {
+        parse_time(raw).map(TimeFilter::Before)
+    }
+
+    pub fn after(raw: &str) -> Result<Self, Error> {
+        parse_time(raw).map(TimeFilter::After)
+    }
+
+    pub fn applies_to(&self, modified: SystemTime) -> bool {
+        match *self {
+            TimeFilter::Before(limit) => modified <= limit,
+            TimeFilter::After(limit) => modified >= limit,
+        }
+    }
+}
+
+// accepts a duration counted back from now (2h, 3d, 1week) or a UTC date (2024-05-01, 2024-05-01T13:30:00)
+fn parse_time(raw: &str) -> Result<SystemTime, Error> {
+    if let Ok(duration) = humantime::parse_duration(raw) {
+        return Ok(SystemTime::now()
+            .checked_sub(duration)
+            .unwrap_or(SystemTime::UNIX_EPOCH));
+    }
+
+    humantime::parse_rfc3339_weak(raw)
+        .or_else(|_| humantime::parse_rfc3339_weak(&format!("{} 00:00:00", raw)))
+        .map_err(|_| {
+            anyhow!(
+                "Invalid time '{}', expected a duration (2h, 3d) or a date (2024-05-01)",
+                raw
+            )
+        })
+}
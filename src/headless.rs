@@ -0,0 +1,164 @@
+use std::{
+    collections::{HashMap, HashSet},
+    io::{self, BufWriter, Write},
+    path::Path,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+        mpsc,
+    },
+    thread,
+};
+
+use anyhow::Result;
+
+use crate::{
+    args::{self, Args},
+    entry::Entry,
+    exit_codes::ExitCode,
+    matcher::is_same_or_inside,
+    sort::{self, SortMode},
+    tui::AppEvent,
+};
+
+// runs one search without the ui and writes the matches to stdout, one per line
+pub fn run(args: Args, type_definitions: HashMap<String, Vec<String>>) -> Result<ExitCode> {
+    // the ui searches whatever is left, a script should know a path was wrong
+    let missing_paths = args::missing_paths(args.paths());
+    if !missing_paths.is_empty() {
+        for path in &missing_paths {
+            let expanded_path = args::expand_tilde(path.to_string_lossy().to_string());
+            let reason = if Path::new(&expanded_path).exists() {
+                "Not a directory"
+            } else {
+                "No such directory"
+            };
+            eprintln!("[rfui] {}: {}", path.display(), reason);
+        }
+        return Ok(ExitCode::GeneralError("Missing search paths".to_string()));
+    }
+
+    let sort_mode = args.sort;
+    let reverse = args.reverse;
+    let is_sorted = sort_mode.is_some() || reverse;
+    // the watcher reports an edited file again when it still matches, print it once
+    let mut printed = args.watch.then(HashSet::new);
+
+    let (tx, rx) = mpsc::channel();
+    let stop_flag = Arc::new(AtomicBool::new(false));
+    let stop_flag_clone = Arc::clone(&stop_flag);
+
+    let scan = thread::spawn(move || args::build_and_scan(args, type_definitions, tx, stop_flag_clone));
+
+    let mut stdout = BufWriter::new(io::stdout().lock());
+    let mut sorted = Vec::new();
+    let mut match_count = 0;
+    let mut is_watching = false;
+
+    for event in rx {
+        let entry = match event {
+            AppEvent::SearchResult(entry) | AppEvent::WatchAdded(entry) => entry,
+            // matches again after this is new output
+            AppEvent::WatchRemoved(path) => {
+                if let Some(printed) = printed.as_mut() {
+                    printed.retain(|target: &String| !is_same_or_inside(target, &path));
+                }
+                continue;
+            }
+            AppEvent::WalkError(error) => {
+                eprintln!("[rfui] {}: {}", error.path, error.reason);
+                continue;
+            }
+            AppEvent::Error(message) => {
+                eprintln!("[rfui] {}", message);
+                continue;
+            }
+            // with --watch the scan is over here, so the sort has everything it needs
+            AppEvent::Watching => {
+                is_watching = true;
+                if write_sorted(&mut stdout, &mut sorted, sort_mode, reverse).is_err() {
+                    stop_flag.store(true, Ordering::Relaxed);
+                    return Ok(ExitCode::Success);
+                }
+                stdout.flush()?;
+                continue;
+            }
+            _ => continue,
+        };
+
+        if printed
+            .as_mut()
+            .is_some_and(|printed| !printed.insert(entry.target.clone()))
+        {
+            continue;
+        }
+
+        match_count += 1;
+
+        // a sort needs every match first
+        if is_sorted && !is_watching {
+            sorted.push(entry);
+            continue;
+        }
+
+        // while watching, show new matches right away
+        let written = write_entry(&mut stdout, &entry)
+            .and_then(|_| if is_watching { stdout.flush() } else { Ok(()) });
+        if written.is_err() {
+            // the reader went away (`| head`), nothing left to do
+            stop_flag.store(true, Ordering::Relaxed);
+            return Ok(ExitCode::Success);
+        }
+    }
+
+    if write_sorted(&mut stdout, &mut sorted, sort_mode, reverse).is_err() {
+        return Ok(ExitCode::Success);
+    }
+    stdout.flush().ok();
+
+    // the channel closes once the scan is over, so this doesn't block
+    match scan.join() {
+        Ok(Ok(_)) => {}
+        Ok(Err(scan_error)) => return Err(scan_error),
+        // the panic message is already on stderr
+        Err(_) => {
+            return Ok(ExitCode::GeneralError(
+                "The search stopped unexpectedly".to_string(),
+            ));
+        }
+    }
+
+    if match_count == 0 {
+        Ok(ExitCode::NoMatches)
+    } else {
+        Ok(ExitCode::Success)
+    }
+}
+
+// writes and empties whatever was held back for the sort
+fn write_sorted(
+    stdout: &mut impl Write,
+    sorted: &mut Vec<Entry>,
+    sort_mode: Option<SortMode>,
+    reverse: bool,
+) -> io::Result<()> {
+    if sorted.is_empty() {
+        return Ok(());
+    }
+
+    sort::sort_entries(sorted, sort_mode.unwrap_or_default());
+    if reverse {
+        sorted.reverse();
+    }
+
+    for entry in sorted.drain(..) {
+        write_entry(stdout, &entry)?;
+    }
+
+    Ok(())
+}
+
+fn write_entry(stdout: &mut impl Write, entry: &Entry) -> io::Result<()> {
+    // labels only help in the ui, a script wants paths it can open
+    writeln!(stdout, "{}", entry.unlabeled())
+}
@@ -0,0 +1,507 @@
+use std::{
+    collections::HashMap,
+    env,
+    fs::{self, File},
+    io::{BufReader, BufWriter},
+    path::{Component, Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Sender},
+        Arc, LazyLock, Mutex, PoisonError,
+    },
+    time::SystemTime,
+};
+
+use anyhow::{Context, Result, anyhow};
+use ignore::{DirEntry, WalkBuilder, WalkState};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    args::{self, Type},
+    config::Config,
+    entry::PathFormat,
+    exit_codes::ExitCode,
+    file_system,
+    pattern::PatternSet,
+    tui::AppEvent,
+    walk,
+};
+
+const INDEX_DIR: &str = "rfui/index";
+
+// indexes already read this session with the mtime of their file, live search
+// looks them up on every keystroke and parsing a big one costs about as much as a walk
+static LOADED: LazyLock<Mutex<HashMap<PathBuf, LoadedIndex>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+type LoadedIndex = (SystemTime, Arc<Index>);
+
+#[derive(Debug, Clone, Copy, clap::ValueEnum)]
+pub enum IndexAction {
+    Build,
+    Update,
+}
+
+// exactly what a default search would see
+#[derive(Serialize, Deserialize)]
+pub struct Index {
+    root: PathBuf,
+    // keyed by the path relative to the root, `/` separated, "" for the root itself
+    dirs: HashMap<String, IndexedDir>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct IndexedDir {
+    modified: SystemTime,
+    entries: Vec<IndexedEntry>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct IndexedEntry {
+    name: String,
+    kind: EntryKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+enum EntryKind {
+    File,
+    Directory,
+    Symlink,
+    Other,
+}
+
+pub fn run(action: IndexAction, paths: &[PathBuf]) -> Result<ExitCode> {
+    let roots = if paths.is_empty() {
+        vec![env::current_dir()?]
+    } else {
+        paths
+            .iter()
+            .map(|path| PathBuf::from(args::expand_tilde(path.to_string_lossy().to_string())))
+            .collect()
+    };
+
+    for root in roots {
+        let root = root
+            .canonicalize()
+            .with_context(|| format!("Could not index {}", root.display()))?;
+
+        let existing = match action {
+            IndexAction::Build => None,
+            IndexAction::Update => Index::load(&root).ok(),
+        };
+
+        let (index, dirs_read) = match existing {
+            Some(index) => index.update(),
+            None => {
+                let index = Index::build(&root)?;
+                let dirs_read = index.dirs.len();
+                (index, dirs_read)
+            }
+        };
+        index.save()?;
+
+        eprintln!(
+            "Indexed {} entries under {} ({} of {} directories read)",
+            index.entry_count(),
+            root.display(),
+            dirs_read,
+            index.dirs.len()
+        );
+    }
+
+    Ok(ExitCode::Success)
+}
+
+// every option that needs file metadata or other ignore rules still walks the disk
+pub fn supports(config: &Config) -> bool {
+    !config.show_hidden
+        && !config.search_contents
+        && !config.search_archives
+        && !config.watch
+        && !config.follow_links
+        && config.same_file_system
+        && config.read_dot_ignore
+        && config.read_vcs_ignore
+        && config.read_global_ignore
+        && config.read_rfui_ignore
+        && config.size_filters.is_empty()
+        && config.time_filters.is_empty()
+        && config.file_types.is_empty()
+        && config
+            .kind
+            .iter()
+            .all(|kind| matches!(kind, Type::File | Type::Directory | Type::Symlink))
+}
+
+// an index covers its root and everything below it, None unless every root is covered
+pub fn find_all(roots: &[PathBuf]) -> Option<Vec<(PathBuf, Arc<Index>, String)>> {
+    find_all_with(roots, Index::load_cached)
+}
+
+fn find_all_with(
+    roots: &[PathBuf],
+    load: impl Fn(&Path) -> Result<Arc<Index>>,
+) -> Option<Vec<(PathBuf, Arc<Index>, String)>> {
+    // nothing to look up, the walker reports the missing paths
+    if roots.is_empty() {
+        return None;
+    }
+
+    roots
+        .iter()
+        .map(|root| {
+            let canonical = root.canonicalize().ok()?;
+            canonical.ancestors().find_map(|ancestor| {
+                let index = load(ancestor).ok()?;
+                let start = to_key(canonical.strip_prefix(ancestor).ok()?);
+
+                // hidden and ignored directories like `target/` aren't in the index,
+                // searching one directly still has to walk it
+                index
+                    .dirs
+                    .contains_key(&start)
+                    .then(|| (root.clone(), index, start))
+            })
+        })
+        .collect()
+}
+
+pub fn scan(
+    indexes: Vec<(PathBuf, Arc<Index>, String)>,
+    config: &Config,
+    patterns: &PatternSet,
+    tx: Sender<AppEvent>,
+    should_stop_flag: Arc<AtomicBool>,
+) -> Result<ExitCode> {
+    let roots = indexes.iter().map(|(root, _, _)| root.clone()).collect::<Vec<_>>();
+    let format = PathFormat::new(config.path_style, &roots);
+
+    for (root, index, start) in &indexes {
+        let overrides = walk::build_overrides(root, &config.excludes)?;
+        let mut pending = vec![(start.clone(), PathBuf::new())];
+
+        while let Some((key, relative_dir)) = pending.pop() {
+            if should_stop_flag.load(Ordering::Relaxed) {
+                return Ok(ExitCode::Success);
+            }
+
+            let Some(dir) = index.dirs.get(&key) else {
+                continue;
+            };
+            let depth = relative_dir.components().count() + 1;
+
+            for entry in &dir.entries {
+                let relative_path = relative_dir.join(&entry.name);
+                let path = root.join(&relative_path);
+                let is_dir = entry.kind == EntryKind::Directory;
+
+                if overrides
+                    .as_ref()
+                    .is_some_and(|overrides| overrides.matched(&path, is_dir).is_ignore())
+                {
+                    continue;
+                }
+
+                if is_dir && config.max_depth.is_none_or(|max_depth| depth < max_depth) {
+                    pending.push((join_key(&key, &entry.name), relative_path.clone()));
+                }
+
+                if !entry.is_wanted(config) {
+                    continue;
+                }
+
+                let target = if config.full_path {
+                    file_system::osstr_to_bytes(relative_path.as_os_str())
+                } else {
+                    file_system::osstr_to_bytes(entry.name.as_ref())
+                };
+                if !patterns.is_match(&target) {
+                    continue;
+                }
+
+                // a closed channel means the app started another search
+                if tx
+                    .send(AppEvent::SearchResult(format.entry(&path, "")))
+                    .is_err()
+                {
+                    return Ok(ExitCode::Success);
+                }
+            }
+        }
+    }
+
+    tx.send(AppEvent::SearchComplete).ok();
+
+    Ok(ExitCode::Success)
+}
+
+impl Index {
+    fn build(root: &Path) -> Result<Self> {
+        let (tx, rx) = mpsc::channel();
+
+        index_walker(root).build_parallel().run(|| {
+            let tx = tx.clone();
+            Box::new(move |entry| {
+                if let Ok(entry) = entry {
+                    tx.send(entry).ok();
+                }
+                WalkState::Continue
+            })
+        });
+        drop(tx);
+
+        let mut dirs: HashMap<String, IndexedDir> = HashMap::new();
+        for entry in rx {
+            let Ok(relative_path) = entry.path().strip_prefix(root) else {
+                continue;
+            };
+
+            if entry.file_type().is_some_and(|file_type| file_type.is_dir()) {
+                let modified = modified_time(entry.path()).unwrap_or(SystemTime::UNIX_EPOCH);
+                dirs.entry(to_key(relative_path))
+                    .or_insert_with(IndexedDir::empty)
+                    .modified = modified;
+            }
+
+            if entry.depth() > 0 {
+                let parent = relative_path.parent().map(to_key).unwrap_or_default();
+                dirs.entry(parent)
+                    .or_insert_with(IndexedDir::empty)
+                    .entries
+                    .push(IndexedEntry::from(&entry));
+            }
+        }
+
+        if !dirs.contains_key("") {
+            return Err(anyhow!("Could not read {}", root.display()));
+        }
+
+        Ok(Self {
+            root: root.to_path_buf(),
+            dirs,
+        })
+    }
+
+    // a directory's mtime only changes when entries are added, removed or renamed in it,
+    // so unchanged directories keep their listing and only their subdirectories are checked
+    fn update(mut self) -> (Self, usize) {
+        let mut dirs = HashMap::new();
+        let mut dirs_read = 0;
+        let mut pending = vec![String::new()];
+
+        while let Some(key) = pending.pop() {
+            let path = self.root.join(&key);
+            let Some(modified) = modified_time(&path) else {
+                continue;
+            };
+
+            let dir = match self.dirs.remove(&key) {
+                Some(dir) if dir.modified == modified => dir,
+                _ => {
+                    dirs_read += 1;
+                    IndexedDir {
+                        modified,
+                        entries: list_dir(&path),
+                    }
+                }
+            };
+
+            pending.extend(
+                dir.entries
+                    .iter()
+                    .filter(|entry| entry.kind == EntryKind::Directory)
+                    .map(|entry| join_key(&key, &entry.name)),
+            );
+            dirs.insert(key, dir);
+        }
+
+        self.dirs = dirs;
+        (self, dirs_read)
+    }
+
+    fn load(root: &Path) -> Result<Self> {
+        let file = File::open(index_path(root)?)?;
+        let index: Self = serde_json::from_reader(BufReader::new(file))?;
+
+        // the file name is only a hash of the root
+        if index.root != root {
+            return Err(anyhow!("Index belongs to {}", index.root.display()));
+        }
+
+        Ok(index)
+    }
+
+    // a rebuilt or updated index gets a new mtime and is read again
+    fn load_cached(root: &Path) -> Result<Arc<Self>> {
+        let modified = fs::metadata(index_path(root)?)?.modified()?;
+
+        let mut loaded = LOADED.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some((loaded_modified, index)) = loaded.get(root) {
+            if *loaded_modified == modified {
+                return Ok(Arc::clone(index));
+            }
+        }
+
+        let index = Arc::new(Self::load(root)?);
+        loaded.insert(root.to_path_buf(), (modified, Arc::clone(&index)));
+
+        Ok(index)
+    }
+
+    fn save(&self) -> Result<()> {
+        let path = index_path(&self.root)?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Could not create {}", parent.display()))?;
+        }
+
+        // write next to it and swap, so a search never reads a half written index
+        let tmp_path = path.with_extension("tmp");
+        let file = File::create(&tmp_path)
+            .with_context(|| format!("Could not write {}", tmp_path.display()))?;
+        serde_json::to_writer(BufWriter::new(file), self)?;
+        fs::rename(&tmp_path, &path)?;
+
+        Ok(())
+    }
+
+    fn entry_count(&self) -> usize {
+        self.dirs.values().map(|dir| dir.entries.len()).sum()
+    }
+}
+
+impl IndexedDir {
+    fn empty() -> Self {
+        Self {
+            modified: SystemTime::UNIX_EPOCH,
+            entries: Vec::new(),
+        }
+    }
+}
+
+impl IndexedEntry {
+    fn from(entry: &DirEntry) -> Self {
+        let kind = match entry.file_type() {
+            _ if entry.path_is_symlink() => EntryKind::Symlink,
+            Some(file_type) if file_type.is_dir() => EntryKind::Directory,
+            Some(file_type) if file_type.is_file() => EntryKind::File,
+            _ => EntryKind::Other,
+        };
+
+        Self {
+            name: entry.file_name().to_string_lossy().to_string(),
+            kind,
+        }
+    }
+
+    fn is_wanted(&self, config: &Config) -> bool {
+        let kind_matches = config.kind.is_empty()
+            || config.kind.iter().any(|kind| match kind {
+                Type::File => self.kind == EntryKind::File,
+                Type::Directory => self.kind == EntryKind::Directory,
+                Type::Symlink => self.kind == EntryKind::Symlink,
+                _ => false,
+            });
+
+        kind_matches
+            && (config.extensions.is_empty()
+                || (self.kind == EntryKind::File
+                    && walk::name_has_extension(&self.name, &config.extensions)))
+    }
+}
+
+// same rules as a default search
+fn index_walker(root: &Path) -> WalkBuilder {
+    let mut builder = WalkBuilder::new(root);
+    builder
+        .follow_links(false)
+        .same_file_system(true)
+        .add_custom_ignore_filename(walk::RFUI_IGNORE_FILENAME);
+    builder
+}
+
+fn list_dir(path: &Path) -> Vec<IndexedEntry> {
+    // parent ignore files are still read, so the rules match a full build
+    index_walker(path)
+        .max_depth(Some(1))
+        .build()
+        .flatten()
+        .filter(|entry| entry.depth() == 1)
+        .map(|entry| IndexedEntry::from(&entry))
+        .collect()
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
+
+fn index_path(root: &Path) -> Result<PathBuf> {
+    let cache_dir = dirs::cache_dir().ok_or_else(|| anyhow!("Could not find the cache directory"))?;
+    Ok(cache_dir
+        .join(INDEX_DIR)
+        .join(format!("{:016x}.json", hash(root))))
+}
+
+// FNV-1a, unlike DefaultHasher it stays the same across builds
+fn hash(path: &Path) -> u64 {
+    path.to_string_lossy()
+        .bytes()
+        .fold(0xcbf29ce484222325, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+        })
+}
+
+fn to_key(relative_path: &Path) -> String {
+    relative_path
+        .components()
+        .filter_map(|component| match component {
+            Component::Normal(name) => Some(name.to_string_lossy()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn join_key(key: &str, name: &str) -> String {
+    if key.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}/{}", key, name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roots_left_out_of_the_index_are_walked() {
+        let root = env::temp_dir().join(format!("rfui-index-test-{}", std::process::id()));
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::create_dir_all(root.join("target/debug")).unwrap();
+        fs::write(root.join(".ignore"), "target/\n").unwrap();
+        fs::write(root.join("target/debug/out.o"), "").unwrap();
+        let root = root.canonicalize().unwrap();
+
+        let load = |path: &Path| {
+            if path == root {
+                Index::build(&root).map(Arc::new)
+            } else {
+                Err(anyhow!("No index for {}", path.display()))
+            }
+        };
+
+        let covered = find_all_with(&[root.join("src")], load);
+        let ignored = find_all_with(&[root.join("target")], load);
+        fs::remove_dir_all(&root).ok();
+
+        assert!(covered.is_some_and(|indexes| indexes[0].2 == "src"));
+        assert!(ignored.is_none());
+    }
+
+    #[test]
+    fn no_roots_use_no_index() {
+        let load = |path: &Path| Err(anyhow!("No index for {}", path.display()));
+        assert!(find_all_with(&[], load).is_none());
+    }
+}
@@ -90,6 +90,11 @@ impl Input {
         }
     }
 
+    pub fn set_text(&mut self, text: String) {
+        self.char_index = text.chars().count();
+        self.text = text;
+    }
+
     pub fn clear_input(&mut self) {
         self.char_index = 0;
         self.text.clear();
@@ -21,6 +21,32 @@ impl Default for KeyMap {
 pub struct Config {
     #[serde(default)]
     pub keymap: KeyMap,
+
+    // layered over `keymap` when started with --pick
+    #[serde(default)]
+    pub pick_keymap: KeyMap,
+
+    #[serde(default)]
+    pub types: HashMap<String, Vec<String>>,
+
+    #[serde(default)]
+    pub search: SearchConfig,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct SearchConfig {
+    pub live: bool,
+    pub debounce_ms: u64,
+}
+
+impl Default for SearchConfig {
+    fn default() -> Self {
+        Self {
+            live: false,
+            debounce_ms: 150,
+        }
+    }
 }
 
 impl<'de> Deserialize<'de> for KeyMap {
@@ -1,26 +1,45 @@
 mod action;
+mod archive;
 mod args;
+mod cli;
 mod config;
+mod content;
+mod entry;
+mod errors;
 mod exit_codes;
 mod file_system;
+mod filter;
+mod headless;
+mod index;
 mod input;
 mod keypress;
 mod matcher;
+mod pattern;
+mod pick;
 mod preview;
 mod results;
+mod sort;
 mod tui;
 mod walk;
+mod watch;
 
-use std::{sync::mpsc};
+use std::{
+    fs,
+    io::{self, IsTerminal},
+    sync::mpsc,
+};
 
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, anyhow};
 
 use crate::{
+    cli::Cli,
     exit_codes::ExitCode,
     keypress::Config,
-    tui::{App, AppEvent},
+    tui::{App, AppEvent, Startup},
 };
 
+const USER_CONFIG_PATH: &str = "rfui/config.toml";
+
 fn main() {
     let result = run();
     match result {
@@ -33,18 +52,88 @@ fn main() {
 }
 
 fn run() -> Result<ExitCode> {
+    let cli = Cli::from_env();
+    if let Some(action) = cli.index {
+        return index::run(action, &cli.paths());
+    }
+
     let config = load_config()?;
 
+    if cli.print || (cli.has_search() && !io::stdout().is_terminal()) {
+        if !cli.has_search() {
+            return Err(anyhow!("--print needs a pattern, try `rfui . --print`"));
+        }
+        return headless::run(cli.search, config.types);
+    }
+
+    let paths = cli.paths();
+    let search = if cli.has_search() {
+        Some(cli.search)
+    } else if cli.pick && cli.query.is_none() {
+        // there's nothing to pick from without a search, so list everything
+        Some(cli.search.listing_all())
+    } else {
+        None
+    };
+    let startup = Startup {
+        paths,
+        search,
+        query: cli.query,
+        filter: cli.filter,
+        pick: cli.pick,
+    };
+
     let channel = mpsc::channel::<AppEvent>();
+    let mut app = App::new(channel, config, startup)?;
+
+    if cli.pick {
+        let mut terminal = pick::init()?;
+        let exit_code = app.run(&mut terminal);
+        pick::restore();
+
+        let exit_code = exit_code?;
+        pick::print(app.picked())?;
+        return Ok(exit_code);
+    }
+
     let mut terminal = ratatui::init();
 
-    App::new(channel, config)?.run(&mut terminal)?;
+    let exit_code = app.run(&mut terminal)?;
     ratatui::restore();
 
-    Ok(ExitCode::Success)
+    Ok(exit_code)
 }
 
 fn load_config() -> Result<Config> {
     let default_config = include_str!("../default_config.toml");
-    toml::from_str(&default_config).context("Error parsing config.toml")
+    let mut config: toml::Table =
+        toml::from_str(default_config).context("Error parsing config.toml")?;
+
+    // user settings are layered on top of the defaults
+    if let Some(path) = dirs::config_dir().map(|dir| dir.join(USER_CONFIG_PATH)) {
+        if path.is_file() {
+            let user_config = fs::read_to_string(&path)
+                .with_context(|| format!("Error reading {}", path.display()))?;
+            let user_config: toml::Table = toml::from_str(&user_config)
+                .with_context(|| format!("Error parsing {}", path.display()))?;
+            merge_tables(&mut config, user_config);
+        }
+    }
+
+    toml::Value::Table(config)
+        .try_into()
+        .context("Error parsing config.toml")
+}
+
+fn merge_tables(base: &mut toml::Table, overrides: toml::Table) {
+    for (key, value) in overrides {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(base_table)), toml::Value::Table(table)) => {
+                merge_tables(base_table, table)
+            }
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
 }
@@ -1,7 +1,12 @@
-use std::sync::Arc;
+use std::{
+    collections::HashSet,
+    path::MAIN_SEPARATOR,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use nucleo::{
-    Config, Item, Nucleo, Status,
+    Config, Nucleo, Status,
     pattern::{CaseMatching, Normalization},
 };
 use ratatui::{
@@ -9,17 +14,56 @@ use ratatui::{
     text::{Line, Span},
 };
 
+use crate::{
+    entry::Entry,
+    sort::{SortMode, Sorter},
+};
+
 const MATCHER_TICK_RATE: u64 = 2;
+const FRESH_SYMBOL: &str = "+ ";
+const MARK_SYMBOL: &str = "* ";
+// no need to sort again for every batch of results a big scan streams in
+const RESORT_INTERVAL: Duration = Duration::from_millis(250);
+
+// the index is the item's position in nucleo, which is what the sorter orders
+struct Indexed {
+    index: u32,
+    entry: Entry,
+}
 
 pub struct Matcher {
-    inner: Nucleo<String>,
+    inner: Nucleo<Indexed>,
+    pushed: u32,
     last_pattern: String,
     status: Status,
+    // targets of the entries the watcher added
+    fresh: HashSet<String>,
+    // targets of every item, built on the watcher's first change so later ones don't copy the items
+    known: Option<HashSet<String>>,
+    // in the order they were marked, the targets are for lookups while rendering.
+    // marks outlive a restart, so live search doesn't drop them on every keystroke
+    marked: Vec<Entry>,
+    marked_targets: HashSet<String>,
+    sort_mode: SortMode,
+    reverse: bool,
+    sorter: Sorter,
+    // items handed to the sorter so far
+    synced: u32,
+    sort_id: u64,
+    // the sort that's still running and what it was asked for
+    waiting_for: Option<(u64, SortMode, bool)>,
+    // item indexes in sorted order, and the order they're in
+    sorted: Vec<u32>,
+    sorted_for: Option<(SortMode, bool)>,
+    sort_pending: bool,
+    last_sorted: Option<Instant>,
+    // the filter changed and nucleo's snapshot still holds the old matches
+    filter_pending: bool,
 }
 
 impl Matcher {
     pub fn new() -> Self {
-        let matcher: Nucleo<String> = Nucleo::new(Config::DEFAULT, Arc::new(|| {}), None, 1);
+        let matcher: Nucleo<Indexed> = Nucleo::new(Config::DEFAULT, Arc::new(|| {}), None, 1);
 
         let status = Status {
             changed: false,
@@ -28,17 +72,40 @@ impl Matcher {
 
         Self {
             inner: matcher,
+            pushed: 0,
             last_pattern: String::new(),
             status,
+            fresh: HashSet::new(),
+            known: None,
+            marked: Vec::new(),
+            marked_targets: HashSet::new(),
+            sort_mode: SortMode::default(),
+            reverse: false,
+            sorter: Sorter::new(),
+            synced: 0,
+            sort_id: 0,
+            waiting_for: None,
+            sorted: Vec::new(),
+            sorted_for: None,
+            sort_pending: false,
+            last_sorted: None,
+            filter_pending: false,
         }
     }
 
     pub fn is_empty(&self) -> bool {
-        self.get_item(0).iter().count() == 0
+        self.get_item(0).is_none()
     }
 
-    pub fn get_item(&self, index: usize) -> Option<Item<String>> {
-        self.inner.snapshot().get_matched_item(index as u32)
+    pub fn get_item(&self, index: usize) -> Option<Entry> {
+        let snapshot = self.inner.snapshot();
+        let item = if self.is_sorted() {
+            snapshot.get_item(*self.sorted.get(index)?)
+        } else {
+            snapshot.get_matched_item(index as u32)
+        };
+
+        item.map(|item| item.data.entry.clone())
     }
 
     pub fn get_matched_items_count(&self) -> u32 {
@@ -51,31 +118,269 @@ impl Matcher {
 
     pub fn tick(&mut self) {
         self.status = self.inner.tick(MATCHER_TICK_RATE);
+        self.sort_pending |= self.status.changed;
+        if !self.status.running {
+            self.filter_pending = false;
+        }
+
+        while let Some(sorted) = self.sorter.try_result() {
+            if let Some((id, sort_mode, reverse)) = self.waiting_for {
+                if sorted.id == id {
+                    self.sorted = sorted.order;
+                    self.sorted_for = Some((sort_mode, reverse));
+                    self.waiting_for = None;
+                }
+            }
+        }
+
+        let resort_due = self
+            .last_sorted
+            .is_none_or(|last_sorted| last_sorted.elapsed() >= RESORT_INTERVAL);
+
+        if self.is_custom_order()
+            && self.sort_pending
+            && !self.filter_pending
+            && self.waiting_for.is_none()
+            && resort_due
+        {
+            self.resort();
+        }
     }
 
     pub fn restart(&mut self) {
         self.inner.restart(true);
+        self.pushed = 0;
+        self.fresh.clear();
+        self.known = None;
+        self.reset_sort();
+    }
+
+    // item indexes changed, the sorter starts over and old results are thrown away
+    fn reset_sort(&mut self) {
+        self.sorter.reset();
+        self.synced = 0;
+        self.waiting_for = None;
+        self.sorted.clear();
+        self.sorted_for = None;
+        self.last_sorted = None;
+    }
+
+    pub fn get_sort_label(&self) -> Option<String> {
+        self.is_custom_order().then(|| {
+            let arrow = if self.reverse { " (reversed)" } else { "" };
+            format!("{}{}", self.sort_mode.label(), arrow)
+        })
+    }
+
+    pub fn set_sort(&mut self, sort_mode: SortMode, reverse: bool) {
+        self.sort_mode = sort_mode;
+        self.reverse = reverse;
+        self.sort_pending = true;
+        self.waiting_for = None;
+        self.last_sorted = None;
+    }
+
+    pub fn cycle_sort(&mut self) {
+        self.set_sort(self.sort_mode.next(), self.reverse);
+    }
+
+    pub fn toggle_reverse(&mut self) {
+        self.set_sort(self.sort_mode, !self.reverse);
+    }
+
+    pub fn toggle_mark(&mut self, entry: Entry) {
+        if self.marked_targets.remove(&entry.target) {
+            self.marked.retain(|marked| marked.target != entry.target);
+        } else {
+            self.marked_targets.insert(entry.target.clone());
+            self.marked.push(entry);
+        }
+    }
+
+    // everything the current filter matches, on top of what's already marked
+    pub fn mark_all(&mut self) {
+        let snapshot = self.inner.snapshot();
+        let matched = if self.is_sorted() {
+            self.sorted
+                .iter()
+                .filter_map(|index| snapshot.get_item(*index))
+                .map(|item| item.data.entry.clone())
+                .collect::<Vec<_>>()
+        } else {
+            snapshot
+                .matched_items(..)
+                .map(|item| item.data.entry.clone())
+                .collect()
+        };
+
+        for entry in matched {
+            if self.marked_targets.insert(entry.target.clone()) {
+                self.marked.push(entry);
+            }
+        }
+    }
+
+    pub fn clear_marks(&mut self) {
+        self.marked.clear();
+        self.marked_targets.clear();
+    }
+
+    pub fn get_marked(&self) -> &[Entry] {
+        &self.marked
+    }
+
+    // nucleo's own order is by score, or discovery order for an empty filter
+    fn is_custom_order(&self) -> bool {
+        self.sort_mode != SortMode::Score || self.reverse
+    }
+
+    // until the first sort for the current order is back, results stay in nucleo's order
+    fn is_sorted(&self) -> bool {
+        self.is_custom_order() && self.sorted_for == Some((self.sort_mode, self.reverse))
+    }
+
+    // only items the sorter hasn't seen yet are copied, the matches go as indexes
+    fn resort(&mut self) {
+        let snapshot = self.inner.snapshot();
+
+        let item_count = snapshot.item_count();
+        if self.synced < item_count {
+            let new_entries = (self.synced..item_count)
+                .filter_map(|index| snapshot.get_item(index))
+                .map(|item| item.data.entry.clone())
+                .collect();
+            self.sorter.add(new_entries);
+            self.synced = item_count;
+        }
+
+        let matched = snapshot
+            .matched_items(..)
+            .map(|item| item.data.index)
+            .collect();
+
+        self.sort_id += 1;
+        self.sorter
+            .sort(self.sort_id, matched, self.sort_mode, self.reverse);
+        self.waiting_for = Some((self.sort_id, self.sort_mode, self.reverse));
+        self.sort_pending = false;
+        self.last_sorted = Some(Instant::now());
+    }
+
+    // entries reported by the watcher after the walk finished
+    pub fn apply_changes(&mut self, added: Vec<Entry>, removed: &[String]) {
+        // make sure everything pushed so far is part of the snapshot
+        self.tick();
+
+        let mut known = self.known.take().unwrap_or_else(|| {
+            let snapshot = self.inner.snapshot();
+            (0..snapshot.item_count())
+                .filter_map(|index| snapshot.get_item(index))
+                .map(|item| item.data.entry.target.clone())
+                .collect()
+        });
+
+        let is_removed = |target: &String| removed.iter().any(|path| is_same_or_inside(target, path));
+
+        if !removed.is_empty() {
+            self.fresh.retain(|target| !is_removed(target));
+            self.marked.retain(|entry| !is_removed(&entry.target));
+            self.marked_targets.retain(|target| !is_removed(target));
+
+            // nucleo can't drop single items, so it only starts over when one of them is gone
+            if known.iter().any(is_removed) {
+                known.retain(|target| !is_removed(target));
+
+                let snapshot = self.inner.snapshot();
+                let items = (0..snapshot.item_count())
+                    .filter_map(|index| snapshot.get_item(index))
+                    .filter(|item| !is_removed(&item.data.entry.target))
+                    .map(|item| (item.data.index, item.data.entry.clone()))
+                    .collect::<Vec<_>>();
+
+                // the kept items move up, so does their place in the current order
+                let mut new_indexes = vec![None; snapshot.item_count() as usize];
+                for (new_index, (old_index, _)) in items.iter().enumerate() {
+                    new_indexes[*old_index as usize] = Some(new_index as u32);
+                }
+                let sorted = self
+                    .sorted
+                    .iter()
+                    .filter_map(|index| new_indexes.get(*index as usize).copied().flatten())
+                    .collect::<Vec<_>>();
+                let sorted_for = self.sorted_for;
+
+                self.inner.restart(true);
+                self.pushed = 0;
+                self.reset_sort();
+                self.sorted = sorted;
+                self.sorted_for = sorted_for;
+                self.sort_pending = true;
+                for (_, entry) in items {
+                    self.push(entry);
+                }
+            }
+        }
+
+        for entry in added {
+            if known.insert(entry.target.clone()) {
+                self.fresh.insert(entry.target.clone());
+                self.push(entry);
+            }
+        }
+
+        self.known = Some(known);
     }
 
-    pub fn push(&mut self, search_result: String) {
-        self.inner.injector().push(search_result, |s, cols| {
-            cols[0] = s.as_str().into();
+    pub fn push(&mut self, search_result: Entry) {
+        let item = Indexed {
+            index: self.pushed,
+            entry: search_result,
+        };
+        self.pushed += 1;
+
+        self.inner.injector().push(item, |item, cols| {
+            cols[0] = item.entry.display.as_str().into();
         });
     }
 
     pub fn get_results(&mut self, pattern: &str, width: u16, offset: u32, height: u32) -> Vec<Line> {
         let snapshot = self.inner.snapshot();
+
+        if self.is_sorted() {
+            return self
+                .sorted
+                .iter()
+                .skip(offset as usize)
+                .take(height as usize)
+                .filter_map(|index| snapshot.get_item(*index))
+                .map(|item| self.render_item(&item.data.entry, pattern, width))
+                .collect::<Vec<_>>();
+        }
+
         let matched_item_count = self.get_matched_items_count();
 
         snapshot
             .matched_items(offset..(offset + height).min(matched_item_count))
-            .map(|item| {
-                let truncated_text = truncate_text(item.data.to_string(), width);
-                self.highlight_fuzzy_match(&truncated_text, pattern)
-            })
+            .map(|item| self.render_item(&item.data.entry, pattern, width))
             .collect::<Vec<_>>()
     }
 
+    fn render_item(&self, entry: &Entry, pattern: &str, width: u16) -> Line<'_> {
+        let mut symbols = Vec::new();
+        if self.marked_targets.contains(&entry.target) {
+            symbols.push(Span::styled(MARK_SYMBOL, Style::default().fg(Color::Magenta).bold()));
+        }
+        if self.fresh.contains(&entry.target) {
+            symbols.push(Span::styled(FRESH_SYMBOL, Style::default().fg(Color::Green).bold()));
+        }
+
+        let symbols_width = symbols.iter().map(|symbol| symbol.width()).sum::<usize>();
+        let truncated_text = truncate_text(entry.display.clone(), width.saturating_sub(symbols_width as u16));
+        let mut line = self.highlight_fuzzy_match(&truncated_text, pattern);
+        line.spans.splice(0..0, symbols);
+        line
+    }
+
     pub fn find_fuzzy_match(&mut self, current_pattern: &str) {
         if current_pattern != self.last_pattern {
             self.inner.pattern.reparse(
@@ -86,6 +391,14 @@ impl Matcher {
                 current_pattern.starts_with(&self.last_pattern),
             );
             self.last_pattern = current_pattern.to_string();
+
+            // the sorted order still holds entries the new filter may drop
+            self.sorted.clear();
+            self.sorted_for = None;
+            self.waiting_for = None;
+            self.sort_pending = true;
+            self.filter_pending = true;
+            self.last_sorted = None;
         }
     }
 
@@ -130,6 +443,11 @@ impl Matcher {
     }
 }
 
+pub fn is_same_or_inside(item: &str, path: &str) -> bool {
+    item.strip_prefix(path)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with(MAIN_SEPARATOR))
+}
+
 fn truncate_text(text: String, width: u16) -> String {
     if text.len() < width as usize {
         return text;
@@ -151,3 +469,48 @@ fn truncate_text(text: String, width: u16) -> String {
         second_half.iter().collect::<String>()
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(path: &str) -> Entry {
+        Entry::new(path.to_string(), path.to_string())
+    }
+
+    #[test]
+    fn marks_survive_a_restart() {
+        let mut matcher = Matcher::new();
+        matcher.push(entry("src/main.rs"));
+        matcher.push(entry("src/tui.rs"));
+        matcher.tick();
+        matcher.toggle_mark(entry("src/tui.rs"));
+
+        matcher.restart();
+        matcher.push(entry("src/tui.rs"));
+        matcher.tick();
+
+        assert_eq!(matcher.get_marked(), [entry("src/tui.rs")]);
+        let line = matcher.render_item(&entry("src/tui.rs"), "", 80);
+        assert_eq!(line.spans[0].content, MARK_SYMBOL);
+    }
+
+    #[test]
+    fn sorted_results_follow_the_filter() {
+        let mut matcher = Matcher::new();
+        matcher.push(entry("src/main.rs"));
+        matcher.push(entry("src/tui.rs"));
+        matcher.set_sort(SortMode::Name, false);
+        while !matcher.is_sorted() {
+            matcher.tick();
+        }
+
+        matcher.find_fuzzy_match("tui");
+        matcher.tick();
+        for index in 0..2 {
+            if let Some(item) = matcher.get_item(index) {
+                assert_eq!(item, entry("src/tui.rs"));
+            }
+        }
+    }
+}
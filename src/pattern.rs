@@ -0,0 +1,32 @@
+use regex::bytes::{Match, Regex};
+
+// the positional pattern plus every --and / --not pattern
+pub struct PatternSet {
+    primary: Regex,
+    required: Vec<Regex>,
+    excluded: Vec<Regex>,
+}
+
+impl PatternSet {
+    pub fn new(primary: Regex, required: Vec<Regex>, excluded: Vec<Regex>) -> Self {
+        Self {
+            primary,
+            required,
+            excluded,
+        }
+    }
+
+    pub fn is_match(&self, haystack: &[u8]) -> bool {
+        self.find(haystack).is_some()
+    }
+
+    // position comes from the primary pattern, the others only decide whether it counts
+    pub fn find<'h>(&self, haystack: &'h [u8]) -> Option<Match<'h>> {
+        let found = self.primary.find(haystack)?;
+
+        let accepted = self.required.iter().all(|regexp| regexp.is_match(haystack))
+            && !self.excluded.iter().any(|regexp| regexp.is_match(haystack));
+
+        accepted.then_some(found)
+    }
+}
@@ -0,0 +1,47 @@
+use std::{
+    io::{self, BufWriter, Stderr, Write},
+    panic,
+};
+
+use anyhow::Result;
+use crossterm::{
+    execute,
+    terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
+};
+use ratatui::{Terminal, backend::CrosstermBackend};
+
+use crate::entry::Entry;
+
+// stdout belongs to whoever reads the pick, e.g. `$(rfui --pick)`, so the ui is drawn on stderr
+pub type PickTerminal = Terminal<CrosstermBackend<Stderr>>;
+
+pub fn init() -> Result<PickTerminal> {
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        restore();
+        hook(info);
+    }));
+
+    enable_raw_mode()?;
+    execute!(io::stderr(), EnterAlternateScreen)?;
+
+    Ok(Terminal::new(CrosstermBackend::new(io::stderr()))?)
+}
+
+pub fn restore() {
+    if let Err(e) = disable_raw_mode() {
+        eprintln!("Failed to restore the terminal: {}", e);
+    }
+    execute!(io::stderr(), LeaveAlternateScreen).ok();
+}
+
+// one entry per line, cwd relative like the clipboard gets them
+pub fn print(entries: &[Entry]) -> Result<()> {
+    let mut stdout = BufWriter::new(io::stdout().lock());
+    for entry in entries {
+        writeln!(stdout, "{}", entry.target)?;
+    }
+    stdout.flush()?;
+
+    Ok(())
+}
@@ -10,7 +10,7 @@ use ratatui::{
     widgets::{Block, BorderType, Paragraph},
 };
 
-use crate::results::Results;
+use crate::{archive, entry::Entry, results::Results};
 
 static BINARY_EXTENSIONS: LazyLock<HashSet<String>> = LazyLock::new(|| {
     let json_str = embed_file::embed_string!("../binary-extensions.json");
@@ -27,8 +27,10 @@ pub struct PreviewContent {
 }
 
 impl PreviewContent {
-    pub fn new(path: String, scroll_pos: usize, height: u16) -> Self {
-        let content = get_preview(path, scroll_pos, height).unwrap_or_else(|e| format!("{}", e));
+    pub fn new(entry: Option<&Entry>, scroll_pos: usize, height: u16) -> Self {
+        let content = entry
+            .map(|entry| get_preview(entry, scroll_pos, height).unwrap_or_else(|e| format!("{}", e)))
+            .unwrap_or_default();
 
         let line_count = content.lines().count();
 
@@ -66,8 +68,8 @@ impl Preview {
     }
 
     pub fn render_preview(&mut self, results: &mut Results, frame: &mut Frame, right_area: Rect) {
-        let path = self.get_preview_path(results);
-        let preview_content = PreviewContent::new(path, self.vertical_scroll, self.height);
+        let entry = self.get_preview_entry(results);
+        let preview_content = PreviewContent::new(entry.as_ref(), self.vertical_scroll, self.height);
         self.set_height(right_area.height.saturating_sub(2)); // -2 bcs of borders
 
         let preview_block = Block::bordered()
@@ -128,18 +130,24 @@ impl Preview {
         self.horizontal_scroll = 0;
     }
 
-    pub fn get_preview_path(&mut self, results: &Results) -> String {
-        let current_path = results
-            .get_selected()
-            .map(|item| item.data.as_str())
+    pub fn get_preview_entry(&mut self, results: &Results) -> Option<Entry> {
+        let entry = results.get_selected();
+        let current_path = entry
+            .as_ref()
+            .map(|entry| entry.target.as_str())
             .unwrap_or_default();
 
         if current_path != self.prev_path {
             self.prev_path = current_path.to_string();
             self.reset_scroll_position();
+
+            // center the matched line of a content search result
+            if let Some(line) = entry.as_ref().and_then(|entry| entry.line) {
+                self.vertical_scroll = line.saturating_sub(self.height as usize / 2 + 1);
+            }
         }
 
-        self.prev_path.clone()
+        entry
     }
 }
 
@@ -152,21 +160,31 @@ pub fn should_preview_with_bat(file_path: &str) -> bool {
     }
 }
 
-pub fn get_preview(path: String, scroll_pos: usize, height: u16) -> Result<String> {
-    if !should_preview_with_bat(&path) {
+pub fn get_preview(entry: &Entry, scroll_pos: usize, height: u16) -> Result<String> {
+    let path = entry.path();
+    if archive::is_member_path(path) {
+        bail!("Archive members are not available for preview");
+    }
+
+    if !should_preview_with_bat(path) {
         bail!("Binary file not available for preview");
     }
 
-    let output = Command::new("bat")
+    let mut command = Command::new("bat");
+    command
         .arg("-n")
         .arg("--color=always")
         .arg(format!(
             "--line-range={}:{}",
             scroll_pos + 1,
             scroll_pos + height as usize
-        ))
-        .arg(path)
-        .output()?;
+        ));
+
+    if let Some(line) = entry.line {
+        command.arg(format!("--highlight-line={}", line));
+    }
+
+    let output = command.arg(path).output()?;
 
     Ok(String::from_utf8_lossy(&output.stdout).into_owned())
 }
@@ -1,6 +1,5 @@
 use std::time::Instant;
 
-use nucleo::Item;
 use ratatui::{
     Frame,
     layout::Rect,
@@ -9,7 +8,7 @@ use ratatui::{
     widgets::{Block, BorderType, List, ListState, Padding},
 };
 
-use crate::{input::Input, matcher::Matcher, tui::AppEvent};
+use crate::{entry::Entry, input::Input, matcher::Matcher, tui::AppEvent};
 
 const POINTER_SYMBOL: &str = "> ";
 
@@ -34,7 +33,13 @@ impl Results {
         }
     }
 
-    pub fn get_status_msg(&self, app_event: &AppEvent) -> String {
+    pub fn get_status_msg(&self, app_event: &AppEvent, error_count: usize) -> String {
+        let errors = match error_count {
+            0 => String::new(),
+            1 => " • 1 error (Ctrl+E)".to_string(),
+            count => format!(" • {} errors (Ctrl+E)", count),
+        };
+
         match app_event {
             AppEvent::SearchResult(_) => {
                 let dots = match (self.animation_start.elapsed().as_millis() / 500) % 3 {
@@ -42,11 +47,17 @@ impl Results {
                     1 => "..",
                     _ => "...",
                 };
-                format!(" Scanning files{} ", dots)
+                format!(" Scanning files{}{} ", dots, errors)
             }
             AppEvent::SearchComplete => format!(
-                " {} files found • ↑↓ navigate • Esc exits ",
-                self.matcher.get_matched_items_count()
+                " {} files found{} • ↑↓ navigate • Esc exits ",
+                self.matcher.get_matched_items_count(),
+                errors
+            ),
+            AppEvent::Watching => format!(
+                " {} files found{} • watching for changes • ↑↓ navigate • Esc exits ",
+                self.matcher.get_matched_items_count(),
+                errors
             ),
             _ => "".to_string(),
         }
@@ -62,13 +73,21 @@ impl Results {
         results_area: Rect,
         input: &Input,
         app_event: &AppEvent,
+        error_count: usize,
     ) {
         self.set_height(results_area.height as usize);
-        let title = Line::from(self.title.as_str()).bold();
+        let mut title = self.title.clone();
+        if !self.matcher.get_marked().is_empty() {
+            title.push_str(&format!("• {} marked ", self.matcher.get_marked().len()));
+        }
+        if let Some(sort_label) = self.matcher.get_sort_label() {
+            title.push_str(&format!("• sorted by {} ", sort_label));
+        }
+        let title = Line::from(title).bold();
 
         let results_block = Block::bordered()
             .title(title)
-            .title_bottom(self.get_status_msg(app_event))
+            .title_bottom(self.get_status_msg(app_event, error_count))
             .border_type(BorderType::Rounded)
             .border_style(Style::default().fg(Color::Cyan))
             .padding(Padding::horizontal(1));
@@ -92,15 +111,40 @@ impl Results {
         self.list_state.selected().unwrap_or(0)
     }
 
-    pub fn get_selected(&self) -> Option<Item<String>> {
+    pub fn get_selected(&self) -> Option<Entry> {
         self.matcher.get_item(self.absolute_selected())
     }
 
+    // the marked entries, or the selected one when nothing is marked
+    pub fn get_chosen(&self) -> Vec<Entry> {
+        match self.matcher.get_marked() {
+            [] => self.get_selected().into_iter().collect(),
+            marked => marked.to_vec(),
+        }
+    }
+
+    pub fn toggle_mark(&mut self) {
+        if let Some(selected_entry) = self.get_selected() {
+            self.matcher.toggle_mark(selected_entry);
+            self.select_next();
+        }
+    }
+
     pub fn move_to_top(&mut self) {
         self.offset = 0;
         self.list_state.select_first();
     }
 
+    pub fn cycle_sort(&mut self) {
+        self.move_to_top();
+        self.matcher.cycle_sort();
+    }
+
+    pub fn toggle_reverse(&mut self) {
+        self.move_to_top();
+        self.matcher.toggle_reverse();
+    }
+
     pub fn restart(&mut self) {
         self.offset = 0;
         self.matcher.restart();
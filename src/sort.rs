@@ -0,0 +1,228 @@
+use std::{
+    cmp::Reverse,
+    collections::HashMap,
+    fs,
+    path::{Component, Path},
+    sync::mpsc::{self, Receiver, Sender},
+    thread,
+    time::SystemTime,
+};
+
+use crate::{archive, entry::Entry};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum SortMode {
+    #[default]
+    Score,
+    Name,
+    Path,
+    Size,
+    #[value(alias = "modified")]
+    Mtime,
+    Depth,
+}
+
+impl SortMode {
+    const ALL: [SortMode; 6] = [
+        SortMode::Score,
+        SortMode::Name,
+        SortMode::Path,
+        SortMode::Size,
+        SortMode::Mtime,
+        SortMode::Depth,
+    ];
+
+    pub fn next(self) -> Self {
+        Self::ALL[(self as usize + 1) % Self::ALL.len()]
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SortMode::Score => "score",
+            SortMode::Name => "name",
+            SortMode::Path => "path",
+            SortMode::Size => "size",
+            SortMode::Mtime => "mtime",
+            SortMode::Depth => "depth",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct FileInfo {
+    size: u64,
+    modified: SystemTime,
+}
+
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+enum SortKey {
+    Text(String),
+    Count(usize),
+    // biggest and newest first, like `ls -S` and `ls -t`
+    Size(Reverse<u64>),
+    Time(Reverse<SystemTime>),
+}
+
+enum SortJob {
+    Add(Vec<Entry>),
+    Reset,
+    Sort {
+        id: u64,
+        matched: Vec<u32>,
+        mode: SortMode,
+        reverse: bool,
+    },
+}
+
+// `order` holds the indexes of the matched items, sorted
+pub struct Sorted {
+    pub id: u64,
+    pub order: Vec<u32>,
+}
+
+// sorts on its own thread, size and mtime need a metadata call per entry and the ui
+// can't wait for that on a big tree. entries are sent once and keep their sort key
+pub struct Sorter {
+    jobs: Sender<SortJob>,
+    results: Receiver<Sorted>,
+}
+
+impl Sorter {
+    pub fn new() -> Self {
+        let (jobs, job_receiver) = mpsc::channel();
+        let (result_sender, results) = mpsc::channel();
+        thread::spawn(move || run_sorter(job_receiver, result_sender));
+
+        Self { jobs, results }
+    }
+
+    // entries have to be added in item order, the first one is item 0
+    pub fn add(&self, entries: Vec<Entry>) {
+        self.jobs.send(SortJob::Add(entries)).ok();
+    }
+
+    pub fn reset(&self) {
+        self.jobs.send(SortJob::Reset).ok();
+    }
+
+    pub fn sort(&self, id: u64, matched: Vec<u32>, mode: SortMode, reverse: bool) {
+        self.jobs
+            .send(SortJob::Sort {
+                id,
+                matched,
+                mode,
+                reverse,
+            })
+            .ok();
+    }
+
+    pub fn try_result(&self) -> Option<Sorted> {
+        self.results.try_recv().ok()
+    }
+}
+
+// the thread ends once the `Sorter` is dropped
+fn run_sorter(jobs: Receiver<SortJob>, results: Sender<Sorted>) {
+    let mut entries = Vec::new();
+    let mut keys = Vec::new();
+    let mut keys_mode = None;
+    let mut file_info = HashMap::new();
+
+    for job in jobs {
+        match job {
+            SortJob::Add(new_entries) => entries.extend(new_entries),
+            SortJob::Reset => {
+                entries.clear();
+                keys.clear();
+                file_info.clear();
+            }
+            SortJob::Sort {
+                id,
+                mut matched,
+                mode,
+                reverse,
+            } => {
+                // score order is the order nucleo matched them in
+                if mode != SortMode::Score {
+                    if keys_mode != Some(mode) {
+                        keys.clear();
+                        keys_mode = Some(mode);
+                    }
+                    let start = keys.len().min(entries.len());
+                    keys.extend(
+                        entries[start..]
+                            .iter()
+                            .map(|entry| sort_key(entry, mode, &mut file_info)),
+                    );
+
+                    matched.retain(|index| (*index as usize) < keys.len());
+                    matched.sort_by(|a, b| keys[*a as usize].cmp(&keys[*b as usize]));
+                }
+                if reverse {
+                    matched.reverse();
+                }
+
+                if results.send(Sorted { id, order: matched }).is_err() {
+                    return;
+                }
+            }
+        }
+    }
+}
+
+pub fn sort_entries(entries: &mut [Entry], mode: SortMode) {
+    if mode == SortMode::Score {
+        return;
+    }
+
+    let mut file_info = HashMap::new();
+    entries.sort_by_cached_key(|entry| sort_key(entry, mode, &mut file_info));
+}
+
+fn sort_key(
+    entry: &Entry,
+    mode: SortMode,
+    file_info: &mut HashMap<String, Option<FileInfo>>,
+) -> (SortKey, String, usize) {
+    // content matches sort by their file first, then by line
+    let (path, line) = (entry.path(), entry.line);
+
+    let key = match mode {
+        SortMode::Name => SortKey::Text(file_name(path).to_lowercase()),
+        SortMode::Depth => SortKey::Count(depth(path)),
+        SortMode::Size => {
+            SortKey::Size(Reverse(lookup(path, file_info).map_or(0, |info| info.size)))
+        }
+        SortMode::Mtime => SortKey::Time(Reverse(
+            lookup(path, file_info).map_or(SystemTime::UNIX_EPOCH, |info| info.modified),
+        )),
+        SortMode::Score | SortMode::Path => SortKey::Count(0),
+    };
+
+    (key, path.to_string(), line.unwrap_or(0))
+}
+
+fn file_name(path: &str) -> &str {
+    path.rsplit(['/', '\\']).next().unwrap_or(path)
+}
+
+fn depth(path: &str) -> usize {
+    Path::new(path)
+        .components()
+        .filter(|component| matches!(component, Component::Normal(_)))
+        .count()
+}
+
+fn lookup(path: &str, file_info: &mut HashMap<String, Option<FileInfo>>) -> Option<FileInfo> {
+    // archive members share the metadata of their archive
+    let disk_path = path
+        .split_once(archive::MEMBER_SEPARATOR)
+        .map_or(path, |(archive_path, _)| archive_path);
+
+    *file_info.entry(disk_path.to_string()).or_insert_with(|| {
+        fs::metadata(disk_path).ok().map(|metadata| FileInfo {
+            size: metadata.len(),
+            modified: metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH),
+        })
+    })
+}
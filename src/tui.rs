@@ -3,22 +3,26 @@ use cli_clipboard::{ClipboardContext, ClipboardProvider};
 use crossterm::event::{self, Event as CrosstermEvent};
 use nucleo::pattern::Atom;
 use ratatui::{
-    DefaultTerminal, Frame,
+    Frame, Terminal,
+    backend::Backend,
     layout::{Constraint, Layout, Rect},
     style::{Color, Style, Stylize},
     text::Line,
     widgets::{Block, BorderType, Paragraph, Wrap},
 };
 use std::{
+    path::PathBuf,
     rc::Rc,
-    sync::{atomic::{AtomicBool, Ordering}, mpsc::{Receiver, Sender}, Arc},
+    sync::{atomic::{AtomicBool, Ordering}, mpsc::{self, Receiver, Sender}, Arc},
     thread,
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 use crate::{
     action::Action,
-    args::{self},
+    args::{self, Args},
+    entry::Entry,
+    errors::{ErrorPanel, WalkError},
     exit_codes::ExitCode,
     input::Input,
     keypress::{self, Config},
@@ -37,22 +41,41 @@ const TICK_RATE: Duration = Duration::from_millis(10);
 #[derive(Debug, Clone)]
 pub enum AppEvent {
     Event(CrosstermEvent),
-    SearchResult(String),
+    SearchResult(Entry),
     Error(String),
+    WalkError(WalkError),
     SearchComplete,
+    Watching,
+    WatchAdded(Entry),
+    WatchRemoved(String),
     Tick,
 }
 
+// what rfui was launched with, see `rfui --query` and `rfui --pick`
+#[derive(Debug, Default)]
+pub struct Startup {
+    pub query: Option<String>,
+    // a pattern and flags given on the command line, e.g. `rfui foo src -k f`
+    pub search: Option<Args>,
+    pub filter: Option<String>,
+    pub paths: Vec<PathBuf>,
+    pub pick: bool,
+}
+
 pub struct App {
     sender: Sender<AppEvent>,
     receiver: Receiver<AppEvent>,
     last_app_event: Option<AppEvent>,
     results: Results,
     preview: Preview,
+    errors: ErrorPanel,
     config: Config,
     is_help_screen: bool,
     preview_width: u16,
     stop_flag: Arc<AtomicBool>,
+    pending_search: Option<Instant>,
+    startup: Startup,
+    picked: Vec<Entry>,
     pub input: Input,
 
     #[cfg(target_os = "macos")]
@@ -75,16 +98,26 @@ fn create_clipboard_context() -> Result<ClipboardContext> {
 impl App {
     pub fn new(
         (sender, receiver): (Sender<AppEvent>, Receiver<AppEvent>),
-        config: Config,
+        mut config: Config,
+        startup: Startup,
     ) -> Result<App> {
         let clipboard_ctx = create_clipboard_context()?;
 
+        if startup.pick {
+            let pick_keymap = config.pick_keymap.0.clone();
+            config.keymap.0.extend(pick_keymap);
+        }
+
         Ok(Self {
             last_app_event: None,
             input: Input::default(),
             preview: Preview::new(),
+            errors: ErrorPanel::new(),
             results: Results::new(),
             stop_flag: Arc::new(AtomicBool::new(false)),
+            pending_search: None,
+            startup,
+            picked: Vec::new(),
             is_help_screen: false,
             preview_width: 50,
             clipboard_ctx,
@@ -94,26 +127,67 @@ impl App {
         })
     }
 
-    pub fn run(&mut self, terminal: &mut DefaultTerminal) -> Result<ExitCode> {
-        self.update_and_draw(terminal)?;
-        Ok(ExitCode::Success)
+    pub fn run<B: Backend>(&mut self, terminal: &mut Terminal<B>) -> Result<ExitCode> {
+        if let Some(query) = self.startup.query.take() {
+            self.input.set_text(query);
+            self.handle_search();
+        } else if let Some(args) = self.startup.search.take() {
+            self.start_search();
+            self.scan(args);
+        }
+
+        if let Some(filter) = self.startup.filter.take() {
+            // in live mode the input holds the query, so the filter can't show up there
+            if !self.config.search.live {
+                self.input.set_text(filter.clone());
+            }
+            self.results.move_to_top();
+            self.results.matcher.find_fuzzy_match(&filter);
+        }
+
+        self.update_and_draw(terminal)
+    }
+
+    // what --pick prints once the ui is closed
+    pub fn picked(&self) -> &[Entry] {
+        &self.picked
     }
 
-    fn update_and_draw(&mut self, terminal: &mut DefaultTerminal) -> Result<ExitCode> {
+    fn update_and_draw<B: Backend>(&mut self, terminal: &mut Terminal<B>) -> Result<ExitCode> {
         loop {
+            let mut added = Vec::new();
+            let mut removed = Vec::new();
+
             while let Ok(ref result) = self.receiver.try_recv() {
                 match result {
-                    AppEvent::SearchResult(path) => {
-                        self.results.matcher.push(path.to_string());
+                    AppEvent::SearchResult(entry) => {
+                        self.results.matcher.push(entry.clone());
                     }
                     AppEvent::Error(error_message) => {
                         self.input.set_error(error_message.clone());
                     }
+                    AppEvent::WalkError(error) => {
+                        self.errors.push(error.clone());
+                        continue;
+                    }
+                    AppEvent::WatchAdded(entry) => {
+                        added.push(entry.clone());
+                        continue;
+                    }
+                    AppEvent::WatchRemoved(path) => {
+                        added.retain(|entry| entry.target != *path);
+                        removed.push(path.to_string());
+                        continue;
+                    }
                     _ => {}
                 }
                 self.last_app_event = Some(result.to_owned());
             }
 
+            if !added.is_empty() || !removed.is_empty() {
+                self.results.matcher.apply_changes(added, &removed);
+            }
+
             // handle events
             if let Some(AppEvent::Event(key)) = self.read_with_timeout(TICK_RATE)? {
                 if self.handle_events(key) {
@@ -121,11 +195,23 @@ impl App {
                 }
             }
 
+            if self
+                .pending_search
+                .is_some_and(|deadline| Instant::now() >= deadline)
+            {
+                self.handle_search();
+            }
+
             self.results.matcher.tick();
             self.results.select_first();
             terminal.draw(|frame| self.draw(frame))?;
         }
 
+        // leaving a pick without picking is an abort, like Ctrl+C in other fuzzy finders
+        if self.startup.pick && self.picked.is_empty() {
+            return Ok(ExitCode::KilledBySigint);
+        }
+
         Ok(ExitCode::Success)
     }
 
@@ -135,10 +221,15 @@ impl App {
             return;
         }
 
-        let (left_area, right_area) = self.get_areas(frame, !self.results.matcher.is_empty());
+        let (left_area, right_area) = self.get_areas(
+            frame,
+            !self.results.matcher.is_empty() || self.errors.is_visible,
+        );
 
-        // only render preview if we have results or toggled
-        if !self.results.matcher.is_empty() {
+        // the error panel takes the place of the preview while it's open
+        if self.errors.is_visible {
+            self.errors.render(frame, right_area);
+        } else if !self.results.matcher.is_empty() {
             self.preview
                 .render_preview(&mut self.results, frame, right_area);
         }
@@ -149,7 +240,7 @@ impl App {
         let last_app_event = self.last_app_event.as_ref().unwrap_or(&AppEvent::Tick);
 
         self.results
-            .render_list(frame, results_area, &self.input, last_app_event);
+            .render_list(frame, results_area, &self.input, last_app_event, self.errors.count());
         self.input.render_input(frame, input_areas);
     }
 
@@ -167,6 +258,9 @@ impl App {
                 if self.is_help_screen {
                     self.toggle_help_screen();
                     false
+                } else if self.errors.is_visible {
+                    self.errors.toggle();
+                    false
                 } else {
                     true
                 }
@@ -179,6 +273,14 @@ impl App {
                 self.results.select_previous();
                 false
             }
+            Action::ScrollPreviewUp if self.errors.is_visible => {
+                self.errors.scroll_up();
+                false
+            }
+            Action::ScrollPreviewDown if self.errors.is_visible => {
+                self.errors.scroll_down();
+                false
+            }
             Action::ScrollPreviewUp => {
                 self.preview.scroll_up();
                 false
@@ -219,47 +321,115 @@ impl App {
                 }
                 false
             }
+            Action::Filter if self.config.search.live => {
+                self.schedule_search();
+                false
+            }
             Action::Filter => {
                 self.handle_filter();
                 false
             }
             Action::CopyToClipboard => {
-                if let Some(selected_entry) = self.results.get_selected() {
-                    if let Err(e) = self
-                        .clipboard_ctx
-                        .set_contents(selected_entry.data.to_string())
-                    {
+                let chosen = self.results.get_chosen();
+                if !chosen.is_empty() {
+                    let targets = chosen
+                        .into_iter()
+                        .map(|entry| entry.target)
+                        .collect::<Vec<_>>();
+
+                    if let Err(e) = self.clipboard_ctx.set_contents(targets.join("\n")) {
                         self.sender.send(AppEvent::Error(e.to_string())).unwrap();
                     }
                 }
                 false
             }
+            Action::ToggleMark => {
+                self.results.toggle_mark();
+                false
+            }
+            Action::MarkAll => {
+                self.results.matcher.mark_all();
+                false
+            }
+            Action::ClearMarks => {
+                self.results.matcher.clear_marks();
+                false
+            }
+            // Enter accepts in --pick mode, commands typed in still have to work
+            Action::Accept if self.input.text == "/help" => {
+                self.toggle_help_screen();
+                false
+            }
+            Action::Accept if !self.is_help_screen && !self.errors.is_visible => {
+                self.picked = self.results.get_chosen();
+                !self.picked.is_empty()
+            }
+            Action::CycleSort => {
+                self.results.cycle_sort();
+                false
+            }
+            Action::ReverseSort => {
+                self.results.toggle_reverse();
+                false
+            }
+            Action::ToggleErrors => {
+                self.errors.toggle();
+                false
+            }
             _ => false,
         }
     }
 
     fn handle_search(&mut self) {
-        let stop_flag_clone = Arc::clone(&self.stop_flag);
-        self.start_search(&stop_flag_clone);
-        let tx_clone: Sender<AppEvent> = self.sender.clone();
+        self.pending_search = None;
+        self.start_search();
+
+        // in live mode the input holds the query, so it has to stay
+        if self.config.search.live {
+            self.input.clear_error();
+            if self.input.text.trim().is_empty() {
+                return;
+            }
+        }
 
         match args::parse_input_args(&self.input.text) {
-            Ok(args) => {
-                thread::spawn(move || {
-                    if let Err(scan_error) = args::build_and_scan(args, tx_clone.clone(), stop_flag_clone) {
-                        tx_clone
-                            .send(AppEvent::Error(scan_error.to_string()))
-                            .unwrap();
-                    }
-                });
-            }
+            Ok(args) => self.scan(args),
             Err(parse_error) => {
-                tx_clone
+                self.sender
                     .send(AppEvent::Error(parse_error.to_string()))
                     .unwrap();
             }
         }
-        self.input.clear_input();
+
+        if !self.config.search.live {
+            self.input.clear_input();
+        }
+    }
+
+    fn scan(&mut self, args: Args) {
+        let stop_flag_clone = Arc::clone(&self.stop_flag);
+        let tx_clone: Sender<AppEvent> = self.sender.clone();
+        let type_definitions = self.config.types.clone();
+
+        let args = args.with_default_paths(&self.startup.paths);
+        if args.sort.is_some() || args.reverse {
+            self.results
+                .matcher
+                .set_sort(args.sort.unwrap_or_default(), args.reverse);
+        }
+
+        thread::spawn(move || {
+            if let Err(scan_error) = args::build_and_scan(args, type_definitions, tx_clone.clone(), stop_flag_clone) {
+                tx_clone
+                    .send(AppEvent::Error(scan_error.to_string()))
+                    .ok();
+            }
+        });
+    }
+
+    fn schedule_search(&mut self) {
+        let debounce = Duration::from_millis(self.config.search.debounce_ms);
+        self.pending_search = Some(Instant::now() + debounce);
     }
 
     fn handle_filter(&mut self) {
@@ -267,11 +437,15 @@ impl App {
         self.results.matcher.find_fuzzy_match(&self.input.text);
     }
 
-    fn start_search(&mut self, should_stop_flag: &Arc<AtomicBool>) {
-        should_stop_flag.store(true, Ordering::Relaxed); // stop walker
-        std::thread::sleep(Duration::from_millis(10)); // give old thread time to stop
+    fn start_search(&mut self) {
+        // stop the old walker and drop its channel so nothing it still sends shows up
+        self.stop_flag.store(true, Ordering::Relaxed);
+        self.stop_flag = Arc::new(AtomicBool::new(false));
+        (self.sender, self.receiver) = mpsc::channel();
+        self.last_app_event = None;
+        self.errors.clear();
+
         self.results.restart();
-        should_stop_flag.store(false, Ordering::Relaxed); // go again
         self.results.matcher.find_fuzzy_match("");
     }
 
@@ -332,15 +506,62 @@ fn render_help_screen(frame: &mut Frame) {
         Line::from("   .                         # List all entries in the current working directory"),
         Line::from("   config                    # Find entries containing 'config'"),
         Line::from("   config -k f               # Find only files (not directories)"),
+        Line::from("   . -k e -k x               # Empty entries and executables"),
+        Line::from("   handler -T rust -T !test  # Rust files that aren't tests"),
+        Line::from("   logo --search-archives    # Look inside zip/jar/tar files too"),
+        Line::from("   . -e o -w                 # Watch object files appear during a build"),
         Line::from("   test -d 2                 # Search max 2 directories deep"),
+        Line::from("   src/.*/mod.rs -p          # Match on directory components too"),
+        Line::from("   *.{rs,toml} -g            # Match with a glob instead of a regex"),
+        Line::from("   foo(1).txt -F             # Match brackets and dots literally"),
+        Line::from("   test --and api --not mock # Names with 'test' and 'api' but not 'mock'"),
+        Line::from("   . -e rs -e toml           # Only .rs and .toml files"),
+        Line::from("   log -S +1M -S -100M       # Files between 1MB and 100MB"),
+        Line::from("   . --changed-within 2h     # Entries modified in the last 2 hours"),
+        Line::from("   main -E target -E *.md    # Skip target/ and markdown files"),
+        Line::from("   build --no-ignore-vcs     # Include files ignored by git"),
         Line::from("   log -H                    # Include hidden files"),
+        Line::from("   FooConfig -c              # Find lines mentioning 'FooConfig'"),
+        Line::from("   . -e log --sort size      # Biggest log files first"),
+        Line::from("   . --sort mtime -r         # Oldest entries first"),
+        Line::from("   conf ~/src /etc --relative-to root  # Labeled by root: [src] app/conf.rs"),
         Line::from(""),
         Line::from(" FLAGS:").style(Style::default().fg(Color::Yellow).bold()),
-        Line::from("   -k, --kind <TYPE>         Type: file (f) or directory (d)"),
+        Line::from("   -k, --kind <TYPE>         Type, repeatable: file (f), directory (d),"),
+        Line::from("                             symlink (l), executable (x), empty (e),"),
+        Line::from("                             socket (s), pipe (p), block-device (b),"),
+        Line::from("                             char-device (c)"),
         Line::from("   -d, --max-depth <NUM>     Maximum search depth"),
         Line::from("   -H, --hidden              Include hidden files"),
         Line::from("   -s, --case-sensitive      Case sensitive search"),
         Line::from("   -t, --threads <NUM>       Number of threads"),
+        Line::from("   -c, --contents            Search inside files"),
+        Line::from("   -p, --full-path           Match against the path, not just the name"),
+        Line::from("   -g, --glob                Treat the pattern as a glob"),
+        Line::from("   -F, --fixed-strings       Treat the pattern as a literal string"),
+        Line::from("   --and <PATTERN>           Must also match (repeatable)"),
+        Line::from("   --not <PATTERN>           Must not match (repeatable)"),
+        Line::from("   -e, --extension <EXT>     Filter by file extension (repeatable)"),
+        Line::from("   -S, --size <SIZE>         Filter by size: +10M, -4k, 512b (repeatable)"),
+        Line::from("   --changed-within <TIME>   Modified within 2h, 3d or since a date"),
+        Line::from("   --changed-before <TIME>   Modified before 2h, 3d ago or a date"),
+        Line::from("   -E, --exclude <GLOB>      Skip matching entries and subtrees (repeatable)"),
+        Line::from("   -T, --type <TYPE>         File type (rust, web, ...), !TYPE excludes"),
+        Line::from("   --search-archives         Match members of zip/jar/tar/tar.gz files"),
+        Line::from("   -w, --watch               Keep results updated as files come and go"),
+        Line::from("   --no-ignore               Don't respect any ignore file (incl. .rfuiignore)"),
+        Line::from("   --no-ignore-vcs           Don't respect .gitignore and git excludes"),
+        Line::from("   --no-ignore-dot           Don't respect .ignore files"),
+        Line::from("   --no-global-ignore        Don't respect the global git excludes file"),
+        Line::from("   -u, --unrestricted        Disable all filtering (--no-ignore --hidden)"),
+        Line::from("   -L, --follow              Follow symbolic links"),
+        Line::from("   --cross-filesystems       Descend into other filesystems"),
+        Line::from("   --one-file-system         Stay on the starting filesystem (default)"),
+        Line::from("   --sort <MODE>             Order by score, name, path, size, mtime, depth"),
+        Line::from("   -r, --reverse             Reverse the result order"),
+        Line::from("   -a, --absolute-path       Show results as absolute paths"),
+        Line::from("   --relative-to <BASE>      Show results relative to root or cwd"),
+        Line::from("   --no-index                Walk the disk even if an index exists"),
         Line::from(""),
         Line::from(" NAVIGATION:").style(Style::default().fg(Color::Yellow).bold()),
         Line::from("   ↑/↓                       Navigate results"),
@@ -348,9 +569,17 @@ fn render_help_screen(frame: &mut Frame) {
         Line::from("   Ctrl+K/J                  Scroll preview vertically"),
         Line::from("   Crtl+H/L                  Scroll preview horizontally"),
         Line::from("   Ctrl+U/D                  Resize preview/results window"),
-        Line::from("   Ctrl+Y                    Copy selected entry to clipboard"),
+        Line::from("   Ctrl+Y                    Copy selected (or marked) entries to clipboard"),
+        Line::from("   Tab                       Mark/unmark the selected entry"),
+        Line::from("   Ctrl+A                    Mark every matching entry"),
+        Line::from("   Ctrl+X                    Clear the marks"),
+        Line::from("   Ctrl+S                    Cycle sort mode"),
+        Line::from("   Ctrl+R                    Reverse result order"),
+        Line::from("   Ctrl+E                    Show entries that couldn't be read"),
         Line::from("   Enter                     Execute search"),
-        Line::from("   Esc                       Quit"),
+        Line::from("   Enter (--pick)            Print the selected (or marked) entries and exit"),
+        Line::from("   Ctrl+F (--pick)           Execute search"),
+        Line::from("   Esc, Ctrl+C               Quit"),
         Line::from(""),
         Line::from(" Press esc to return...").style(Style::default().bold()),
     ];
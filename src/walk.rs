@@ -1,18 +1,31 @@
 use anyhow::{Result, anyhow};
 use ignore::WalkState;
+use ignore::overrides::{Override, OverrideBuilder};
+use ignore::types::{Types, TypesBuilder};
 use ignore::{DirEntry, WalkBuilder, WalkParallel};
-use regex::bytes::Regex;
-use std::path::PathBuf;
+use std::borrow::Cow;
+use std::fs::{self, FileType};
+use std::path::{Path, PathBuf};
 use std::result::Result::Ok;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::mpsc::Sender;
 use std::sync::Arc;
 
+use crate::archive;
 use crate::args::Type;
 use crate::config::Config;
+use crate::content;
+use crate::entry::{Entry, PathFormat};
+use crate::errors::WalkError;
 use crate::exit_codes::ExitCode;
 use crate::file_system::{self};
+use crate::filter::{SizeFilter, TimeFilter};
+use crate::pattern::PatternSet;
 use crate::tui::AppEvent;
+use crate::watch::RootWatcher;
+
+// gitignore syntax, takes precedence over .ignore and .gitignore
+pub const RFUI_IGNORE_FILENAME: &str = ".rfuiignore";
 
 pub struct Walker {
     config: Config,
@@ -24,6 +37,11 @@ impl Walker {
     }
 
     pub fn build(&self, paths: &[PathBuf]) -> Result<WalkParallel> {
+        Ok(self.builder(paths, None)?.build_parallel())
+    }
+
+    // with `targets`, only the entries on the way to or below one of them are visited
+    fn builder(&self, paths: &[PathBuf], targets: Option<Vec<PathBuf>>) -> Result<WalkBuilder> {
         if paths.is_empty() {
             return Err(anyhow!("No paths provided for search"));
         }
@@ -34,69 +52,217 @@ impl Walker {
         let mut builder = WalkBuilder::new(first_path);
         builder
             .hidden(!config.show_hidden)
+            .ignore(config.read_dot_ignore)
+            .git_ignore(config.read_vcs_ignore)
+            .git_exclude(config.read_vcs_ignore)
+            .git_global(config.read_global_ignore)
             .max_depth(config.max_depth)
             .ignore_case_insensitive(config.case_sensitive)
-            .follow_links(false)
-            .same_file_system(true)
+            .follow_links(config.follow_links)
+            .same_file_system(config.same_file_system)
             .threads(config.threads);
         // add more config here later on if needed
 
+        if config.read_rfui_ignore {
+            builder.add_custom_ignore_filename(RFUI_IGNORE_FILENAME);
+        }
+
+        // each root anchors its own excludes, and filtered entries aren't descended into
+        let mut excludes = Vec::new();
+        for path in paths {
+            if let Some(overrides) = build_overrides(path, &config.excludes)? {
+                excludes.push((path.clone(), overrides));
+            }
+        }
+
+        if !excludes.is_empty() || targets.is_some() {
+            builder.filter_entry(move |entry| {
+                let on_target_path = targets.as_ref().is_none_or(|targets| {
+                    targets.iter().any(|target| {
+                        target.starts_with(entry.path()) || entry.path().starts_with(target)
+                    })
+                });
+
+                on_target_path && !is_excluded(entry, &excludes)
+            });
+        }
+
+        if !config.file_types.is_empty() {
+            builder.types(self.build_types()?);
+        }
+
         for path in &paths[1..] {
             builder.add(path);
         }
 
-        let walker = builder.build_parallel();
+        Ok(builder)
+    }
+
+    fn build_types(&self) -> Result<Types> {
+        let mut types = TypesBuilder::new();
+        types.add_defaults();
 
-        Ok(walker)
+        for (name, globs) in &self.config.type_definitions {
+            for glob in globs {
+                types
+                    .add(name, glob)
+                    .map_err(|e| anyhow!("Invalid glob for type '{}': {}", name, e))?;
+            }
+        }
+
+        for file_type in &self.config.file_types {
+            match file_type.strip_prefix('!') {
+                Some(negated) => types.negate(negated),
+                None => types.select(file_type),
+            };
+        }
+
+        types.build().map_err(|e| anyhow!("{}", e))
     }
 
     pub fn scan(
         &self,
         paths: Vec<PathBuf>,
-        regexp: Regex,
+        patterns: PatternSet,
         tx: Sender<AppEvent>,
         should_stop_flag: Arc<AtomicBool>
     ) -> Result<ExitCode> {
+        // subscribe before walking so nothing created during the walk is missed
+        let watcher = if self.config.watch {
+            Some(RootWatcher::new(&paths)?)
+        } else {
+            None
+        };
+
         let walker: WalkParallel = self.build(&paths)?;
-        let regexp = &regexp;
-        let config: &Config = &self.config;
+        let format = &PathFormat::new(self.config.path_style, &paths);
+        let patterns = &patterns;
+        let roots = &paths;
         let stop_flag = &should_stop_flag;
 
         walker.run(|| {
             let tx_clone = tx.clone();
             Box::new(move |entry| {
-                if let Ok(entry) = entry {
-                    if stop_flag.load(Ordering::Relaxed) {
-                        return WalkState::Quit;
-                    }
+                if stop_flag.load(Ordering::Relaxed) {
+                    return WalkState::Quit;
+                }
 
-                    if entry.depth() == 0 {
-                        return WalkState::Continue;
-                    }
+                match entry {
+                    Ok(entry) => self.process_entry(&entry, roots, format, patterns, &tx_clone, AppEvent::SearchResult),
+                    // the walker skips what it can't read, just let the user know about it
+                    Err(error) => report_error(&tx_clone, WalkError::from(&error)),
+                }
+            })
+        });
 
-                    if !should_process_entry(&entry, &config.kind) {
-                        return WalkState::Continue;
-                    };
+        tx.send(AppEvent::SearchComplete).ok();
 
-                    if !regexp.is_match(&file_system::osstr_to_bytes(entry.file_name())) {
-                        return WalkState::Continue;
+        if let Some(watcher) = watcher {
+            watcher.run(self, &paths, format, patterns, &tx, &should_stop_flag);
+        }
+
+        Ok(ExitCode::Success)
+    }
+
+    // walks only down to the given paths (and below them), with every filter of a full scan
+    pub fn scan_paths(
+        &self,
+        roots: &[PathBuf],
+        targets: Vec<PathBuf>,
+        format: &PathFormat,
+        patterns: &PatternSet,
+        tx: &Sender<AppEvent>,
+    ) -> WalkState {
+        // the roots already built fine for the full scan
+        let Ok(builder) = self.builder(roots, Some(targets)) else {
+            return WalkState::Continue;
+        };
+
+        for entry in builder.build() {
+            let state = match entry {
+                Ok(entry) => self.process_entry(&entry, roots, format, patterns, tx, AppEvent::WatchAdded),
+                Err(error) => report_error(tx, WalkError::from(&error)),
+            };
+            if state == WalkState::Quit {
+                return WalkState::Quit;
+            }
+        }
+
+        WalkState::Continue
+    }
+
+    fn process_entry(
+        &self,
+        entry: &DirEntry,
+        roots: &[PathBuf],
+        format: &PathFormat,
+        patterns: &PatternSet,
+        tx: &Sender<AppEvent>,
+        to_event: fn(Entry) -> AppEvent,
+    ) -> WalkState {
+        let config = &self.config;
+
+        if entry.depth() == 0 {
+            return WalkState::Continue;
+        }
+
+        // members are matched on their own, before the archive itself gets filtered
+        if config.search_archives
+            && !config.search_contents
+            && entry.file_type().is_some_and(|file_type| file_type.is_file())
+            && archive::is_archive(entry.path())
+        {
+            let members = match archive::list_members(entry.path()) {
+                Ok(members) => members,
+                Err(error) => {
+                    if report_error(tx, WalkError::new(entry.path(), error)) == WalkState::Quit {
+                        return WalkState::Quit;
                     }
+                    Vec::new()
+                }
+            };
+
+            for member in members
+                .iter()
+                .filter(|member| should_process_member(member, patterns, config))
+            {
+                let suffix = format!("{}{}", archive::MEMBER_SEPARATOR, member);
+                if tx.send(to_event(format.entry(entry.path(), &suffix))).is_err() {
+                    return WalkState::Quit;
+                }
+            }
+        }
 
-                    let full_path = entry.path();
-                    let relative_path = file_system::get_relative_path(full_path)
-                        .unwrap_or_else(|| full_path.to_string_lossy().to_string());
+        if !should_process_entry(entry, config) {
+            return WalkState::Continue;
+        };
 
-                    tx_clone
-                        .send(AppEvent::SearchResult(relative_path))
-                        .unwrap();
+        if config.search_contents {
+            if entry.file_type().is_some_and(|file_type| file_type.is_file()) {
+                let content_matches = match content::search_file(entry.path(), patterns) {
+                    Ok(content_matches) => content_matches,
+                    Err(error) => return report_error(tx, WalkError::new(entry.path(), error)),
+                };
+
+                for content_match in content_matches {
+                    if tx.send(to_event(format.content_entry(entry.path(), &content_match))).is_err() {
+                        return WalkState::Quit;
+                    }
                 }
-                WalkState::Continue
-            })
-        });
+            }
+            return WalkState::Continue;
+        }
 
-        tx.send(AppEvent::SearchComplete).unwrap();
+        if !patterns.is_match(&match_target(entry, roots, config.full_path)) {
+            return WalkState::Continue;
+        }
 
-        Ok(ExitCode::Success)
+        // a closed channel means the app started another search
+        if tx.send(to_event(format.entry(entry.path(), ""))).is_err() {
+            return WalkState::Quit;
+        }
+
+        WalkState::Continue
     }
 }
 
@@ -109,16 +275,167 @@ impl Walker {
 //     Ok(String::from_utf8(highlighted.to_vec())?)
 // }
 
-fn should_process_entry(entry: &DirEntry, kind: &Option<Type>) -> bool {
+pub fn build_overrides(root: &Path, excludes: &[String]) -> Result<Option<Override>> {
+    if excludes.is_empty() {
+        return Ok(None);
+    }
+
+    let mut overrides = OverrideBuilder::new(root);
+    for exclude in excludes {
+        overrides
+            .add(&format!("!{}", exclude))
+            .map_err(|e| anyhow!("Invalid exclude pattern '{}': {}", exclude, e))?;
+    }
+
+    Ok(Some(overrides.build()?))
+}
+
+// nested roots use the excludes of the most specific one
+fn is_excluded(entry: &DirEntry, excludes: &[(PathBuf, Override)]) -> bool {
+    let is_dir = entry.file_type().is_some_and(|file_type| file_type.is_dir());
+
+    excludes
+        .iter()
+        .filter(|(root, _)| entry.path().starts_with(root))
+        .max_by_key(|(root, _)| root.components().count())
+        .is_some_and(|(_, overrides)| overrides.matched(entry.path(), is_dir).is_ignore())
+}
+
+fn report_error(tx: &Sender<AppEvent>, error: WalkError) -> WalkState {
+    if tx.send(AppEvent::WalkError(error)).is_err() {
+        WalkState::Quit
+    } else {
+        WalkState::Continue
+    }
+}
+
+fn match_target<'a>(entry: &'a DirEntry, roots: &[PathBuf], full_path: bool) -> Cow<'a, [u8]> {
+    if !full_path {
+        return file_system::osstr_to_bytes(entry.file_name());
+    }
+
+    // roots can be nested, so strip the most specific one
+    let path = entry.path();
+    let relative_path = roots
+        .iter()
+        .filter_map(|root| path.strip_prefix(root).ok())
+        .min_by_key(|relative| relative.components().count())
+        .unwrap_or(path);
+
+    file_system::osstr_to_bytes(relative_path.as_os_str())
+}
+
+fn should_process_entry(entry: &DirEntry, config: &Config) -> bool {
     if let Some(file_type) = entry.file_type() {
-        match kind {
-            Some(n) => match n {
-                Type::File => file_type.is_file(),
-                Type::Directory => file_type.is_dir(),
-            },
-            None => true,
-        }
+        let kind_matches = config.kind.is_empty()
+            || config
+                .kind
+                .iter()
+                .any(|kind| is_of_kind(entry, &file_type, kind));
+
+        // the type matcher only looks at files, directories would all pass it
+        let type_matches = config.file_types.is_empty() || file_type.is_file();
+
+        kind_matches
+            && type_matches
+            && has_wanted_extension(entry, &file_type, &config.extensions)
+            && has_wanted_size(entry, &file_type, &config.size_filters)
+            && has_wanted_mtime(entry, &config.time_filters)
     } else {
         false
     }
 }
+
+fn is_of_kind(entry: &DirEntry, file_type: &FileType, kind: &Type) -> bool {
+    match kind {
+        Type::File => file_type.is_file(),
+        Type::Directory => file_type.is_dir(),
+        Type::Symlink => entry.path_is_symlink(),
+        Type::Executable => {
+            file_type.is_file()
+                && entry
+                    .metadata()
+                    .is_ok_and(|metadata| file_system::is_executable(entry.path(), &metadata))
+        }
+        Type::Empty => {
+            if file_type.is_dir() {
+                fs::read_dir(entry.path()).is_ok_and(|mut children| children.next().is_none())
+            } else {
+                file_type.is_file() && entry.metadata().is_ok_and(|metadata| metadata.len() == 0)
+            }
+        }
+        Type::Socket => file_system::is_socket(file_type),
+        Type::Pipe => file_system::is_pipe(file_type),
+        Type::BlockDevice => file_system::is_block_device(file_type),
+        Type::CharDevice => file_system::is_char_device(file_type),
+    }
+}
+
+fn has_wanted_extension(entry: &DirEntry, file_type: &FileType, extensions: &[String]) -> bool {
+    if extensions.is_empty() {
+        return true;
+    }
+
+    if !file_type.is_file() {
+        return false;
+    }
+
+    name_has_extension(&entry.file_name().to_string_lossy(), extensions)
+}
+
+pub fn name_has_extension(file_name: &str, extensions: &[String]) -> bool {
+    // suffix check instead of Path::extension so `tar.gz` works too
+    let file_name = file_name.to_lowercase();
+    extensions.iter().any(|ext| {
+        file_name
+            .strip_suffix(ext.as_str())
+            .is_some_and(|stem| stem.ends_with('.'))
+    })
+}
+
+// archive members only carry a name, so filters that need metadata never match them
+fn should_process_member(member: &str, patterns: &PatternSet, config: &Config) -> bool {
+    let file_name = member.rsplit('/').next().unwrap_or(member);
+    let target = if config.full_path { member } else { file_name };
+
+    let kind_matches =
+        config.kind.is_empty() || config.kind.iter().any(|kind| matches!(kind, Type::File));
+    let extension_matches =
+        config.extensions.is_empty() || name_has_extension(file_name, &config.extensions);
+
+    kind_matches
+        && extension_matches
+        && config.size_filters.is_empty()
+        && config.time_filters.is_empty()
+        && config.file_types.is_empty()
+        && patterns.is_match(target.as_bytes())
+}
+
+fn has_wanted_size(entry: &DirEntry, file_type: &FileType, size_filters: &[SizeFilter]) -> bool {
+    if size_filters.is_empty() {
+        return true;
+    }
+
+    // directory sizes are filesystem specific, only files make sense here
+    if !file_type.is_file() {
+        return false;
+    }
+
+    entry
+        .metadata()
+        .map(|metadata| size_filters.iter().all(|filter| filter.is_within(metadata.len())))
+        .unwrap_or(false)
+}
+
+fn has_wanted_mtime(entry: &DirEntry, time_filters: &[TimeFilter]) -> bool {
+    if time_filters.is_empty() {
+        return true;
+    }
+
+    entry
+        .metadata()
+        .ok()
+        .and_then(|metadata| metadata.modified().ok())
+        .map(|modified| time_filters.iter().all(|filter| filter.applies_to(modified)))
+        .unwrap_or(false)
+}
@@ -0,0 +1,187 @@
+use std::{
+    collections::HashSet,
+    env,
+    path::{Path, PathBuf},
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, RecvTimeoutError, Sender},
+    },
+    time::Duration,
+};
+
+use anyhow::Result;
+use ignore::WalkState;
+use notify::{
+    Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher,
+    event::{ModifyKind, RenameMode},
+};
+
+use crate::{entry::PathFormat, file_system, pattern::PatternSet, tui::AppEvent, walk::Walker};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+pub struct RootWatcher {
+    // dropping the watcher unsubscribes
+    _watcher: RecommendedWatcher,
+    events: Receiver<notify::Result<Event>>,
+}
+
+#[derive(Default)]
+struct Changes {
+    created: Vec<PathBuf>,
+    removed: Vec<PathBuf>,
+    // edited files can start or stop matching size and time filters
+    modified: Vec<PathBuf>,
+}
+
+impl RootWatcher {
+    pub fn new(roots: &[PathBuf]) -> Result<Self> {
+        let (tx, events) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(tx)?;
+
+        for root in roots {
+            watcher.watch(root, RecursiveMode::Recursive)?;
+        }
+
+        Ok(Self {
+            _watcher: watcher,
+            events,
+        })
+    }
+
+    pub fn run(
+        &self,
+        walker: &Walker,
+        roots: &[PathBuf],
+        format: &PathFormat,
+        patterns: &PatternSet,
+        tx: &Sender<AppEvent>,
+        stop_flag: &Arc<AtomicBool>,
+    ) {
+        if tx.send(AppEvent::Watching).is_err() {
+            return;
+        }
+
+        while !stop_flag.load(Ordering::Relaxed) {
+            let first_event = match self.events.recv_timeout(POLL_INTERVAL) {
+                Ok(event) => event,
+                Err(RecvTimeoutError::Timeout) => continue,
+                Err(RecvTimeoutError::Disconnected) => return,
+            };
+
+            // builds touch many files at once, handle whatever queued up in one go
+            let mut changes = Changes::default();
+            for event in std::iter::once(first_event)
+                .chain(self.events.try_iter())
+                .flatten()
+            {
+                changes.add(event, roots);
+            }
+
+            for path in &changes.removed {
+                let display_path = file_system::get_display_path(path);
+                if tx.send(AppEvent::WatchRemoved(display_path)).is_err() {
+                    return;
+                }
+            }
+
+            if !changes.created.is_empty()
+                && walker.scan_paths(roots, changes.created, format, patterns, tx) == WalkState::Quit
+            {
+                return;
+            }
+
+            if !changes.modified.is_empty()
+                && recheck(walker, roots, changes.modified, format, patterns, tx) == WalkState::Quit
+            {
+                return;
+            }
+        }
+    }
+}
+
+// runs the filters on the modified files again, the ones that don't pass anymore are removed
+fn recheck(
+    walker: &Walker,
+    roots: &[PathBuf],
+    modified: Vec<PathBuf>,
+    format: &PathFormat,
+    patterns: &PatternSet,
+    tx: &Sender<AppEvent>,
+) -> WalkState {
+    let targets = modified
+        .iter()
+        .map(|path| file_system::get_display_path(path))
+        .collect::<Vec<_>>();
+
+    let (recheck_tx, recheck_rx) = mpsc::channel();
+    walker.scan_paths(roots, modified, format, patterns, &recheck_tx);
+    drop(recheck_tx);
+
+    let mut matching = HashSet::new();
+    for event in recheck_rx {
+        if let AppEvent::WatchAdded(entry) = &event {
+            matching.insert(entry.target.clone());
+        }
+        if tx.send(event).is_err() {
+            return WalkState::Quit;
+        }
+    }
+
+    for target in targets {
+        if !matching.contains(&target) && tx.send(AppEvent::WatchRemoved(target)).is_err() {
+            return WalkState::Quit;
+        }
+    }
+
+    WalkState::Continue
+}
+
+impl Changes {
+    fn add(&mut self, event: Event, roots: &[PathBuf]) {
+        let mut paths = event.paths.iter().map(|path| to_root_form(path, roots));
+
+        match event.kind {
+            EventKind::Create(_) | EventKind::Modify(ModifyKind::Name(RenameMode::To)) => {
+                self.created.extend(paths)
+            }
+            EventKind::Remove(_) | EventKind::Modify(ModifyKind::Name(RenameMode::From)) => {
+                self.removed.extend(paths)
+            }
+            // directories only change when their entries do, which comes as its own event
+            EventKind::Modify(ModifyKind::Data(_) | ModifyKind::Metadata(_)) => self
+                .modified
+                .extend(paths.filter(|path| !path.is_dir() && !self.created.contains(path))),
+            EventKind::Modify(ModifyKind::Name(RenameMode::Both)) => {
+                self.removed.extend(paths.next());
+                self.created.extend(paths);
+            }
+            // some platforms can't tell which side of a rename this is
+            EventKind::Modify(ModifyKind::Name(_)) => {
+                for path in paths {
+                    if path.exists() {
+                        self.created.push(path);
+                    } else {
+                        self.removed.push(path);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+// notify reports absolute paths, results are built from the roots as the user typed them
+fn to_root_form(path: &Path, roots: &[PathBuf]) -> PathBuf {
+    let current_dir = env::current_dir().unwrap_or_default();
+
+    roots
+        .iter()
+        .find_map(|root| {
+            path.strip_prefix(current_dir.join(root))
+                .ok()
+                .map(|relative| root.join(relative))
+        })
+        .unwrap_or_else(|| path.to_path_buf())
+}